@@ -0,0 +1,2003 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Terminal rendering of decoded transactions: the report tables, the plain-language
+//! explanation and the batch-level sections
+
+use super::*;
+use colored::{Color, ColoredString, Colorize};
+use prettytable::{Cell, Row, Table, format};
+use std::io;
+
+/// Write a whole number, grouping its digits in threes with commas if `thousands_sep` is set
+fn format_number(n: u64, thousands_sep: bool) -> String {
+    let digits = n.to_string();
+    if !thousands_sep {
+        return digits;
+    }
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Format satoshis as BTC with all 8 decimals, using integer arithmetic so it is exact.
+/// Only the integer part is grouped.
+fn format_btc(sats: u64, thousands_sep: bool) -> String {
+    const SATS_PER_BTC: u64 = 100_000_000;
+    format!(
+        "{}.{:08} BTC",
+        format_number(sats / SATS_PER_BTC, thousands_sep),
+        sats % SATS_PER_BTC
+    )
+}
+
+/// Format an amount in the chosen denomination, e.g. `1.20751 mBTC`
+fn format_amount(sats: u64, denomination: Denomination, thousands_sep: bool) -> String {
+    let amount = bitcoin::Amount::from_sat(sats);
+    match denomination {
+        Denomination::Btc => format_btc(sats, thousands_sep),
+        Denomination::Mbtc => format!(
+            "{} mBTC",
+            amount.to_string_in(bitcoin::Denomination::MilliBitcoin)
+        ),
+        Denomination::Bits => format!("{} bits", amount.to_string_in(bitcoin::Denomination::Bit)),
+        Denomination::Sat => format!("{} satoshis", format_number(sats, thousands_sep)),
+    }
+}
+
+/// Format an amount followed by its satoshi count, unless already shown in satoshis
+fn format_amount_with_sats(sats: u64, denomination: Denomination, thousands_sep: bool) -> String {
+    match denomination {
+        Denomination::Sat => format_amount(sats, denomination, thousands_sep),
+        _ => format!(
+            "{} ({} satoshis)",
+            format_amount(sats, denomination, thousands_sep),
+            format_number(sats, thousands_sep)
+        ),
+    }
+}
+
+/// Like [`format_amount_with_sats`] for a total that may have overflowed
+fn format_total(total: Option<u64>, denomination: Denomination, thousands_sep: bool) -> String {
+    match total {
+        Some(sats) => format_amount_with_sats(sats, denomination, thousands_sep),
+        None => TOTAL_OVERFLOW.to_string(),
+    }
+}
+
+/// Shown instead of a total of satoshi amounts that doesn't fit in a u64
+const TOTAL_OVERFLOW: &str = "overflow (more than 2^64 satoshis)";
+
+/// Border style used for every table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableStyle {
+    /// No borders
+    #[default]
+    Clean,
+    /// Full box-drawing borders
+    Box,
+    /// Pipe-delimited rows that can be pasted into Markdown
+    Markdown,
+}
+
+impl TableStyle {
+    fn format(self) -> format::TableFormat {
+        use format::{FormatBuilder, LinePosition, LineSeparator, consts};
+
+        match self {
+            TableStyle::Clean => *consts::FORMAT_CLEAN,
+            TableStyle::Box => *consts::FORMAT_BOX_CHARS,
+            TableStyle::Markdown => FormatBuilder::new()
+                .column_separator('|')
+                .borders('|')
+                .separator(LinePosition::Title, LineSeparator::new('-', '|', '|', '|'))
+                .padding(1, 1)
+                .build(),
+        }
+    }
+}
+
+impl FromStr for TableStyle {
+    type Err = String;
+
+    /// Parse `clean`, `box` or `markdown`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "clean" => Ok(TableStyle::Clean),
+            "box" => Ok(TableStyle::Box),
+            "markdown" => Ok(TableStyle::Markdown),
+            _ => Err("expected one of clean, box, markdown".to_string()),
+        }
+    }
+}
+
+/// Accumulates the rendered report, applying the color preference. With a sink the
+/// report is handed over piece by piece on [`Renderer::flush`] instead of kept whole.
+struct Renderer<'a> {
+    opts: &'a RenderOptions,
+    out: String,
+    sink: Option<&'a mut dyn io::Write>,
+    /// First error writing to the sink; later writes are skipped
+    error: Option<io::Error>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(opts: &'a RenderOptions) -> Self {
+        Renderer {
+            opts,
+            out: String::new(),
+            sink: None,
+            error: None,
+        }
+    }
+
+    fn streaming(opts: &'a RenderOptions, sink: &'a mut dyn io::Write) -> Self {
+        Renderer {
+            sink: Some(sink),
+            ..Renderer::new(opts)
+        }
+    }
+
+    /// Write what has been rendered so far to the sink, if there is one
+    fn flush(&mut self) {
+        if let Some(sink) = &mut self.sink {
+            if self.error.is_none()
+                && let Err(e) = sink.write_all(self.out.as_bytes())
+            {
+                self.error = Some(e);
+            }
+            self.out.clear();
+        }
+    }
+
+    /// Flush the rest of the report, returning the first write error
+    fn finish(mut self) -> io::Result<()> {
+        self.flush();
+        self.error.map_or(Ok(()), Err)
+    }
+
+    /// Resolve a styled string, dropping the style when color is disabled
+    fn paint(&self, text: ColoredString) -> String {
+        if self.opts.color {
+            text.to_string()
+        } else {
+            text.clear().to_string()
+        }
+    }
+
+    /// Build a table cell, coloring each line separately so styles don't bleed
+    fn cell(&self, text: &str, color: Color) -> Cell {
+        self.styled_cell(text, |line| line.color(color))
+    }
+
+    /// Like [`Renderer::cell`] but with an arbitrary style applied to each line
+    fn styled_cell(&self, text: &str, style: impl Fn(&str) -> ColoredString) -> Cell {
+        let painted: Vec<String> = text.lines().map(|line| self.paint(style(line))).collect();
+        self.painted_cell(&painted)
+    }
+
+    /// Build a cell for disassembled script, coloring opcodes, hashes and keys, and
+    /// other push data apart
+    fn asm_cell(&self, asm: &str) -> Cell {
+        let painted: Vec<String> = asm
+            .lines()
+            .map(|line| {
+                let tokens: Vec<String> = line
+                    .split(' ')
+                    .map(|token| self.paint(token.color(asm_token_color(token))))
+                    .collect();
+                tokens.join(" ")
+            })
+            .collect();
+        self.painted_cell(&painted)
+    }
+
+    /// Build a cell from lines that are already painted
+    fn painted_cell(&self, painted: &[String]) -> Cell {
+        if self.opts.table_style == TableStyle::Markdown {
+            // A markdown row must stay on one line and can't contain bare pipes
+            Cell::new(&painted.join("<br>").replace('|', "\\|"))
+        } else {
+            Cell::new(&painted.join("\n"))
+        }
+    }
+
+    /// Build a `label | value` row with the label in the standard blue
+    fn row(&self, label: &str, value: &str, color: Color) -> Row {
+        Row::new(vec![self.cell(label, Color::Blue), self.cell(value, color)])
+    }
+
+    /// Like [`Renderer::row`] with the value highlighted as script ASM
+    fn asm_row(&self, label: &str, asm: &str) -> Row {
+        Row::new(vec![self.cell(label, Color::Blue), self.asm_cell(asm)])
+    }
+
+    fn line(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Section title followed by a separator, e.g. "📥 INPUTS (2)".
+    /// Quiet mode keeps only the title.
+    fn section(&mut self, emoji: &str, title: &str, count: Option<usize>, color: Color) {
+        let title = self.paint(title.color(color).bold());
+        let mut header = if self.opts.quiet {
+            format!("\n{}", title)
+        } else {
+            format!("\n{} {}", self.paint(emoji.bold()), title)
+        };
+        if let Some(count) = count {
+            header.push_str(&format!(
+                " ({})",
+                self.paint(count.to_string().yellow().bold())
+            ));
+        }
+        self.line(&header);
+        if !self.opts.quiet {
+            let separator = self.paint("─".repeat(70).color(color));
+            self.line(&separator);
+        }
+    }
+
+    /// Per-element heading, e.g. "Input #0"
+    fn item_header(&mut self, name: &str, idx: usize, color: Color) {
+        let header = format!(
+            "\n{} {}",
+            self.paint(name.color(color).bold()),
+            self.paint(format!("#{}", idx).yellow())
+        );
+        self.line(&header);
+    }
+
+    /// Like [`Renderer::item_header`] for an element shown out of order, e.g.
+    /// "Output #7 (display position 1)"
+    fn item_header_at(&mut self, name: &str, idx: usize, position: usize, color: Color) {
+        let header = format!(
+            "\n{} {} {}",
+            self.paint(name.color(color).bold()),
+            self.paint(format!("#{}", idx).yellow()),
+            self.paint(format!("(display position {})", position).dimmed())
+        );
+        self.line(&header);
+    }
+
+    /// Note for the elements left out by a display limit, e.g. "... and 2 more outputs"
+    fn hidden_items(&mut self, total: usize, shown: usize, name: &str, flag: &str) {
+        if total > shown {
+            let hidden = plural(total - shown, &format!("more {}", name));
+            let note =
+                self.paint(format!("... and {} (increase {} to see them)", hidden, flag).dimmed());
+            self.line("");
+            self.line(&note);
+        }
+    }
+
+    /// Empty table in the configured style
+    fn new_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_format(self.opts.table_style.format());
+        if self.opts.table_style == TableStyle::Markdown {
+            // Markdown tables need a header row
+            table.set_titles(Row::new(vec![Cell::new("Field"), Cell::new("Value")]));
+        }
+        table
+    }
+
+    fn table(&mut self, table: &Table) {
+        if self.opts.table_style == TableStyle::Markdown {
+            // Keep the table from being read as part of the preceding paragraph
+            self.out.push('\n');
+        }
+        self.out.push_str(&table.to_string());
+    }
+}
+
+/// Default number of bytes of each witness item shown
+pub const DEFAULT_MAX_WITNESS_PREVIEW: usize = 64;
+
+/// Hex of a witness item, keeping the first and last `max_bytes / 2` bytes of longer
+/// items and honouring `--truncate` if it is stricter
+fn witness_preview(item: &[u8], opts: &RenderOptions) -> String {
+    let preview_chars = opts.max_witness_preview.map(|n| n / 2 * 4);
+    let max_chars = match (opts.truncate, preview_chars) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    truncate_middle(&hex::encode(item), max_chars, item.len())
+}
+
+/// Shorten `text` to `prefix…suffix (M bytes total)` if it exceeds `max` characters.
+/// Applied before coloring, so escape codes are never split.
+fn truncate_middle(text: &str, max: Option<usize>, total_bytes: usize) -> String {
+    let len = text.chars().count();
+    match max {
+        Some(max) if len > max => {
+            let head: String = text.chars().take(max.div_ceil(2)).collect();
+            let tail: String = text.chars().skip(len - max / 2).collect();
+            format!("{}…{} ({} bytes total)", head, tail, total_bytes)
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Color of a token of script ASM: opcodes, then 20- and 32-byte hashes and public keys,
+/// then any other push data. Anything else, such as the note added by truncation, is left
+/// white.
+fn asm_token_color(token: &str) -> Color {
+    if token.starts_with("OP_") {
+        return Color::Magenta;
+    }
+    // A truncated push has the ellipsis in the middle of its hex
+    let data = token.replace('…', "");
+    if data.is_empty() || !data.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Color::White;
+    }
+    match (token.contains('…'), data.len() / 2) {
+        (false, 20 | 32 | 33 | 65) => Color::Yellow,
+        _ => Color::Green,
+    }
+}
+
+/// Describe a witness item, e.g. "Signature (Schnorr)"
+fn decode_witness_item(witness: &[u8]) -> String {
+    match classify_witness_item(witness) {
+        WitnessItemKind::Empty => "Empty witness".to_string(),
+        WitnessItemKind::EcdsaSignature { non_strict: None } => {
+            "Signature (DER, strict ✓)".to_string()
+        }
+        WitnessItemKind::EcdsaSignature {
+            non_strict: Some(reason),
+        } => format!("⚠ non-strict DER ({})", reason),
+        WitnessItemKind::SchnorrSignature => "Signature (Schnorr)".to_string(),
+        WitnessItemKind::CompressedPubkey | WitnessItemKind::UncompressedPubkey => {
+            "Public Key".to_string()
+        }
+        WitnessItemKind::ControlBlock => "Taproot Control Block".to_string(),
+        WitnessItemKind::Script => format!("Script or Data ({} bytes)", witness.len()),
+        WitnessItemKind::Data(len) => format!("Data ({} bytes)", len),
+    }
+}
+
+/// Style an output value by magnitude: dust is dimmed, whole-coin amounts stand out
+fn value_style(output: &bitcoin::TxOut) -> fn(&str) -> ColoredString {
+    if output.value < output.script_pubkey.minimal_non_dust() {
+        |text| text.bright_black().dimmed()
+    } else if output.value > bitcoin::Amount::ONE_BTC {
+        |text| text.bright_yellow().bold()
+    } else {
+        |text| text.yellow()
+    }
+}
+
+/// Proportional bar such as `▇▇▇░░`, `width` characters long, scaled so `max` fills it
+fn value_bar(value: u64, max: u64, width: usize) -> String {
+    let filled = if max == 0 {
+        0
+    } else {
+        ((value as u128 * width as u128 + max as u128 / 2) / max as u128) as usize
+    };
+    "▇".repeat(filled.min(width)) + &"░".repeat(width - filled.min(width))
+}
+
+/// Format an address with its type, flagging addresses that don't round-trip
+fn format_address(output_address: &OutputAddress) -> String {
+    let address = &output_address.address;
+    let mut text = format!("{} ({})", address, get_address_type(address));
+    if !output_address.round_trips {
+        text.push_str(" ⚠ address does not round-trip");
+    }
+    text
+}
+
+/// Render the full report for a transaction as a string
+pub fn render_transaction(tx: &Transaction, opts: &RenderOptions) -> String {
+    let mut r = Renderer::new(opts);
+    render_report(&mut r, tx);
+    r.out
+}
+
+/// Write the full report for a transaction to `out` one input or output at a time, so
+/// transactions with many thousands of outputs never sit in memory as a whole report
+pub fn write_transaction(
+    out: &mut dyn io::Write,
+    tx: &Transaction,
+    opts: &RenderOptions,
+) -> io::Result<()> {
+    let mut r = Renderer::streaming(opts, out);
+    render_report(&mut r, tx);
+    r.finish()
+}
+
+fn render_report(r: &mut Renderer, tx: &Transaction) {
+    let opts = r.opts;
+    let fee = opts.fee(tx);
+
+    // Transaction Overview
+    r.section("📋", "TRANSACTION OVERVIEW", None, Color::Green);
+
+    let mut overview = r.new_table();
+
+    if opts.show_internal_hash {
+        // Txids are displayed byte-reversed; blocks and outpoints store them as-is
+        overview.add_row(r.row(
+            "Txid (display byte order)",
+            &format!(
+                "{} (big-endian, as shown by explorers and RPC)",
+                tx.compute_txid()
+            ),
+            Color::Cyan,
+        ));
+        overview.add_row(r.row(
+            "Txid (internal byte order)",
+            &format!(
+                "{} (little-endian, as serialized in blocks and outpoints)",
+                hex::encode(tx.compute_txid().to_byte_array())
+            ),
+            Color::Cyan,
+        ));
+    } else {
+        overview.add_row(r.row(
+            "Transaction ID (txid)",
+            &tx.compute_txid().to_string(),
+            Color::Cyan,
+        ));
+    }
+    let status = match opts.confirmation {
+        Some(Confirmation::Confirmed { height, time }) => Some((
+            format!(
+                "✓ Confirmed in block {}, block time {} (Unix time)",
+                format_number(height, opts.thousands_sep),
+                time
+            ),
+            Color::Green,
+        )),
+        Some(Confirmation::Unconfirmed) => Some(("⏳ Unconfirmed".to_string(), Color::Yellow)),
+        None => None,
+    };
+    if let Some((text, color)) = status {
+        overview.add_row(r.row("Status", &text, color));
+    }
+    let id_checks = id_checks(tx, opts);
+    if !id_checks.is_empty() {
+        overview.add_row(r.row("wtxid", &tx.compute_wtxid().to_string(), Color::Cyan));
+    }
+    for (label, text, ok) in id_checks {
+        let color = if ok { Color::Green } else { Color::Red };
+        overview.add_row(r.row(label, &text, color));
+    }
+    if tx.is_coinbase() {
+        let height = bip34_height(tx);
+        match &height {
+            Ok(height) => {
+                overview.add_row(r.row(
+                    "Block Height",
+                    &format!("{} (BIP34)", height),
+                    Color::Yellow,
+                ));
+            }
+            Err(e) => {
+                overview.add_row(r.row("Block Height", &format!("unknown, {}", e), Color::White));
+            }
+        }
+        if let Some(expected) = opts.expected_height {
+            overview.add_row(if height == Ok(expected) {
+                r.row("Expected Height", "✓ matches", Color::Green)
+            } else {
+                r.row(
+                    "Expected Height",
+                    &format!("✗ mismatch, expected {}", expected),
+                    Color::Red,
+                )
+            });
+        }
+    }
+    if is_truc(tx) {
+        overview.add_row(r.row("Version", "3 (TRUC)", Color::White));
+        overview.add_row(r.row(
+            "TRUC Policy",
+            "At most 1 unconfirmed parent and 1 unconfirmed child;\nmax 10,000 vB (1,000 vB when spending an unconfirmed TRUC parent)",
+            Color::Black,
+        ));
+        if tx.vsize() > TRUC_MAX_VSIZE {
+            overview.add_row(r.row(
+                "TRUC Warning",
+                &format!(
+                    "⚠ {} vB exceeds the {} vB TRUC size limit",
+                    tx.vsize(),
+                    TRUC_MAX_VSIZE
+                ),
+                Color::Red,
+            ));
+        }
+    } else {
+        overview.add_row(r.row("Version", &format!("{}", tx.version.0), Color::White));
+    }
+    overview.add_row(r.row("Serialization", serialization_format(tx), Color::White));
+    let witness = if is_fully_segwit(tx) {
+        "present".to_string()
+    } else if has_witness(tx) {
+        let with_witness = tx.input.iter().filter(|i| !i.witness.is_empty()).count();
+        format!("present ({} of {} inputs)", with_witness, tx.input.len())
+    } else {
+        "absent".to_string()
+    };
+    overview.add_row(r.row("Witness", &witness, Color::White));
+    if !tx.is_coinbase() {
+        let unsigned = tx.input.iter().filter(|input| is_unsigned(input)).count();
+        let signed = format!("{}/{}", tx.input.len() - unsigned, tx.input.len());
+        if unsigned == 0 {
+            overview.add_row(r.row("Signed Inputs", &signed, Color::White));
+        } else {
+            overview.add_row(r.row(
+                "Signed Inputs",
+                &format!("{} ⚠ {} unsigned", signed, unsigned),
+                Color::Red,
+            ));
+        }
+    }
+    overview.add_row(r.row("Lock Time", &format!("{}", tx.lock_time), Color::White));
+    if tx.lock_time.to_consensus_u32() != 0 {
+        let active = if is_lock_time_active(tx) {
+            "Yes"
+        } else {
+            "No (all input sequences are final)"
+        };
+        overview.add_row(r.row("Lock Time Active", active, Color::White));
+    }
+    overview.add_row(r.row("Size", &format!("{} bytes", tx.total_size()), Color::White));
+    overview.add_row(r.row(
+        "Virtual Size",
+        &format!("{} vBytes", tx.vsize()),
+        Color::White,
+    ));
+    overview.add_row(r.row(
+        "Weight",
+        &format!("{} WU", tx.weight().to_wu()),
+        Color::White,
+    ));
+    overview.add_row(r.row("Sigop Cost", &sigop_cost(tx).to_string(), Color::White));
+    let (standard, reasons) = is_standard(tx);
+    if standard {
+        overview.add_row(r.row("Standard", "Yes", Color::Green));
+    } else {
+        let mut text = "No".to_string();
+        for reason in &reasons {
+            text.push_str(&format!("\n- {}", reason));
+        }
+        overview.add_row(r.row("Standard", &text, Color::Red));
+    }
+    let bip69_sorted = is_bip69_sorted(tx);
+    overview.add_row(r.row(
+        "BIP69 Sorted",
+        if bip69_sorted { "Yes" } else { "No" },
+        Color::White,
+    ));
+    if opts.verbose && !bip69_sorted {
+        let violations = bip69_violations(tx);
+        if !violations.inputs.is_empty() {
+            overview.add_row(r.row(
+                "  Inputs",
+                &format_bip69_violations(&violations.inputs),
+                Color::Yellow,
+            ));
+        }
+        if !violations.outputs.is_empty() {
+            overview.add_row(r.row(
+                "  Outputs",
+                &format_bip69_violations(&violations.outputs),
+                Color::Yellow,
+            ));
+        }
+    }
+
+    r.table(&overview);
+
+    let policy = policy_warnings(tx);
+    let sanity = sanity_warnings(tx);
+    if !policy.is_empty() || !sanity.is_empty() {
+        r.section("⚠️", "WARNINGS", None, Color::Red);
+        let mut table = r.new_table();
+        for warning in &policy {
+            table.add_row(r.row("Policy", &format!("⚠ {}", warning), Color::Red));
+        }
+        for warning in &sanity {
+            table.add_row(r.row("Sanity", &format!("⚠ {}", warning), Color::Red));
+        }
+        r.table(&table);
+    }
+
+    // Inputs
+    r.section("📥", "INPUTS", Some(tx.input.len()), Color::Blue);
+
+    let shown_inputs = opts.max_inputs_display.unwrap_or(usize::MAX);
+    for (idx, input) in tx.input.iter().enumerate().take(shown_inputs) {
+        r.item_header("Input", idx, Color::Blue);
+
+        let mut input_table = r.new_table();
+
+        // Detect and display input type
+        let spent_output = opts.spent_outputs.get(idx).and_then(Option::as_ref);
+        let input_type = match spent_output {
+            Some(prevout) => detect_input_type_with_prevout(input, prevout),
+            None => detect_input_type(input),
+        };
+        input_table.add_row(r.row("  Type", &input_type.to_string(), Color::Cyan));
+        if let Some(prevout) = spent_output {
+            if let Some(address) = output_address(prevout, opts.network) {
+                input_table.add_row(r.row("  Spends", &format_address(&address), Color::Cyan));
+            }
+            input_table.add_row(r.asm_row(
+                "  Prevout Script",
+                &truncate_middle(
+                    &prevout.script_pubkey.to_asm_string(),
+                    opts.truncate,
+                    prevout.script_pubkey.len(),
+                ),
+            ));
+        }
+
+        if let Some(control) = taproot_control_block(input) {
+            input_table.add_row(r.row(
+                "  Internal Key",
+                &control.internal_key.to_string(),
+                Color::Cyan,
+            ));
+            // The low bit of the control block's first byte is the output key's parity
+            let parity = match control.output_key_parity {
+                bitcoin::secp256k1::Parity::Even => "even (control byte bit 0 = 0)",
+                bitcoin::secp256k1::Parity::Odd => "odd (control byte bit 0 = 1)",
+            };
+            input_table.add_row(r.row("  Output Key Parity", parity, Color::Cyan));
+            let depth = control.merkle_branch.len();
+            // A control block holds up to 128 nodes, and 2^128 doesn't fit in a u128
+            let leaves = 1u128
+                .checked_shl(depth as u32)
+                .map_or_else(|| format!("2^{}", depth), |leaves| leaves.to_string());
+            input_table.add_row(r.row(
+                "  Merkle Depth",
+                &format!(
+                    "{} (script tree with up to {} leaves)",
+                    plural(depth, "level"),
+                    leaves
+                ),
+                Color::Cyan,
+            ));
+        }
+
+        if let Some(hint) = lightning_input_hint(input) {
+            input_table.add_row(r.row(
+                "  Hint",
+                &format!("Possible Lightning: {} (heuristic)", hint.label()),
+                Color::Magenta,
+            ));
+        }
+
+        if let Some(package) = &opts.package {
+            for edge in &package.edges {
+                if edge.child == package.index && edge.input == idx {
+                    input_table.add_row(r.row(
+                        "  Package",
+                        &format!("spends output {} of tx #{}", edge.output, edge.parent),
+                        Color::Magenta,
+                    ));
+                }
+            }
+        }
+
+        input_table.add_row(r.row(
+            "  Previous TX",
+            &input.previous_output.txid.to_string(),
+            Color::White,
+        ));
+        input_table.add_row(r.row(
+            "  Output Index",
+            &format!("{}", input.previous_output.vout),
+            Color::White,
+        ));
+        if let Some(amount) = opts.input_amounts.get(idx) {
+            let (text, color) = match amount {
+                Some(amount) => (
+                    format_amount_with_sats(*amount, opts.denomination, opts.thousands_sep),
+                    Color::Yellow,
+                ),
+                None => ("input amount unknown".to_string(), Color::Black),
+            };
+            input_table.add_row(r.row("  Amount", &text, color));
+        }
+        input_table.add_row(r.row(
+            "  Script Length",
+            &format!("{} bytes", input.script_sig.len()),
+            Color::White,
+        ));
+        input_table.add_row(r.row(
+            "  Script Sig",
+            &truncate_middle(
+                &hex::encode(input.script_sig.as_bytes()),
+                opts.truncate,
+                input.script_sig.len(),
+            ),
+            Color::Black,
+        ));
+        if let Some(multisig) = p2sh_multisig(input) {
+            input_table.add_row(r.asm_row(
+                "  Redeem Script",
+                &truncate_middle(
+                    &multisig.redeem_script.to_asm_string(),
+                    opts.truncate,
+                    multisig.redeem_script.len(),
+                ),
+            ));
+            input_table.add_row(r.row(
+                "  Multisig",
+                &format!(
+                    "{}-of-{}\nSignatures provided: {}",
+                    multisig.required, multisig.keys, multisig.signatures
+                ),
+                Color::White,
+            ));
+        }
+        if let Some(reason) = nonstandard_script_sig(input) {
+            input_table.add_row(r.row(
+                "  Warning",
+                &format!("⚠ Non-standard scriptSig: {}", reason),
+                Color::Red,
+            ));
+        }
+        if is_unsigned(input) {
+            input_table.add_row(r.row(
+                "  Warning",
+                "⚠ input not signed (empty scriptSig and witness)",
+                Color::Red,
+            ));
+        }
+        if has_sighash_single_bug(tx, idx) {
+            input_table.add_row(r.row(
+                "  Warning",
+                &format!("⚠ {}", sighash_single_bug_warning(idx)),
+                Color::Red,
+            ));
+        }
+        input_table.add_row(r.row("  Sequence", &format!("{}", input.sequence,), Color::White));
+
+        if let Some(timelock) = input.sequence.to_relative_lock_time() {
+            input_table.add_row(r.row("  Timelock", &format!("{:?}", timelock), Color::White));
+        }
+        if opts.verbose {
+            input_table.add_row(r.row(
+                "  Sequence Breakdown",
+                &sequence_breakdown(input.sequence),
+                Color::White,
+            ));
+        }
+
+        if let Some(script) = revealed_script(input) {
+            for timelock in script_timelocks(script) {
+                input_table.add_row(r.row("  Script Timelock", &timelock, Color::White));
+            }
+        }
+
+        let (base_size, witness_bytes) = input_serialized_size(input);
+        let serialized_size = if witness_bytes > 0 {
+            format!(
+                "{} bytes (base {} + witness {})",
+                base_size + witness_bytes,
+                base_size,
+                witness_bytes
+            )
+        } else {
+            format!("{} bytes", base_size)
+        };
+        input_table.add_row(r.row("  Serialized Size", &serialized_size, Color::White));
+        input_table.add_row(r.row(
+            "  Virtual Size",
+            &format!("{} vBytes", input_vsize(input)),
+            Color::White,
+        ));
+
+        // Witness data if present
+        if !input.witness.is_empty() {
+            input_table.add_row(r.row(
+                "  Witness Items",
+                &format!("{}", input.witness.len()),
+                Color::Yellow,
+            ));
+            // Witness bytes count 1 WU each, versus 4 WU for base data
+            let witness_bytes = witness_size(input);
+            input_table.add_row(r.row(
+                "  Witness Size",
+                &format!(
+                    "{} bytes (weight contribution {} WU)",
+                    witness_bytes, witness_bytes
+                ),
+                Color::Yellow,
+            ));
+
+            for (i, witness_item) in input.witness.iter().enumerate() {
+                let decoded = decode_witness_item(witness_item);
+                input_table.add_row(r.row(
+                    &format!("  Witness [{}]", i),
+                    &format!(
+                        "{}\n    Type: {}\n    Size: {} bytes",
+                        witness_preview(witness_item, opts),
+                        decoded,
+                        witness_item.len()
+                    ),
+                    Color::Yellow,
+                ));
+            }
+        }
+
+        r.table(&input_table);
+        r.flush();
+    }
+    r.hidden_items(
+        tx.input.len(),
+        shown_inputs,
+        "input",
+        "--max-inputs-display",
+    );
+
+    let inscriptions: Vec<(usize, Inscription)> = tx
+        .input
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, input)| Some((idx, input_inscription(input)?)))
+        .collect();
+    if !inscriptions.is_empty() {
+        render_inscriptions(r, &inscriptions);
+    }
+
+    // Outputs
+    r.section("📤", "OUTPUTS", Some(tx.output.len()), Color::Magenta);
+
+    let total_output = total_output_value(tx);
+    let max_output = tx
+        .output
+        .iter()
+        .map(|o| o.value.to_sat())
+        .max()
+        .unwrap_or(0);
+
+    let shown_outputs = opts.max_outputs_display.unwrap_or(usize::MAX);
+    let order = output_display_order(tx, opts.output_order);
+    for (position, &idx) in order.iter().enumerate().take(shown_outputs) {
+        let output = &tx.output[idx];
+        if opts.output_order == OutputOrder::None {
+            r.item_header("Output", idx, Color::Magenta);
+        } else {
+            r.item_header_at("Output", idx, position + 1, Color::Magenta);
+        }
+
+        let mut output_table = r.new_table();
+
+        let value = output.value.to_sat();
+        let mut value_text = format_amount_with_sats(value, opts.denomination, opts.thousands_sep);
+        if let Some(width) = opts.value_bars {
+            value_text.push_str(&format!("  {}", value_bar(value, max_output, width)));
+        }
+        output_table.add_row(Row::new(vec![
+            r.cell("  Value", Color::Blue),
+            r.styled_cell(&value_text, value_style(output)),
+        ]));
+
+        if let Some(reason) = burn_reason(output) {
+            output_table.add_row(r.row(
+                "  Burn",
+                &format!(
+                    "🔥 BURN: {} permanently unspendable ({})",
+                    format_amount(value, opts.denomination, opts.thousands_sep),
+                    reason
+                ),
+                Color::Red,
+            ));
+        }
+
+        if is_anyone_can_spend(output) {
+            output_table.add_row(r.row(
+                "  Warning",
+                &format!(
+                    "⚠ Anyone-Can-Spend: {} can be taken without a signature",
+                    format_amount(value, opts.denomination, opts.thousands_sep)
+                ),
+                Color::Red,
+            ));
+        }
+
+        // Try to extract address from script
+        if let Some(address) = output_address(output, opts.network) {
+            output_table.add_row(r.row("  Address", &format_address(&address), Color::Cyan));
+        } else if let Some(pubkey) = output.script_pubkey.p2pk_public_key() {
+            output_table.add_row(r.row("  Type", "P2PK (Pay-to-Public-Key)", Color::Cyan));
+            output_table.add_row(r.row("  Public Key", &pubkey.to_string(), Color::Cyan));
+            output_table.add_row(r.row(
+                "  Address",
+                &format!(
+                    "{} (P2PKH of the same key, P2PK has no address)",
+                    bitcoin::Address::p2pkh(pubkey, opts.network)
+                ),
+                Color::Cyan,
+            ));
+        }
+
+        if let Some((version, program)) = witness_program(&output.script_pubkey) {
+            output_table.add_row(r.row(
+                "  Witness Program",
+                &format!(
+                    "Witness Version: {}, Program: {}",
+                    version,
+                    truncate_middle(&hex::encode(&program), opts.truncate, program.len())
+                ),
+                Color::Cyan,
+            ));
+        }
+
+        if let Some(version) = future_witness_version(output) {
+            output_table.add_row(r.row(
+                "  Type",
+                &format!("Witness v{} program (future/unknown)", version),
+                Color::Yellow,
+            ));
+            output_table.add_row(r.row(
+                "  Note",
+                "⚠ Anyone-can-spend under current rules until a soft fork defines this version",
+                Color::Red,
+            ));
+        }
+
+        if let Some(address) = &opts.highlight_address
+            && output_pays_to(output, address, opts.network)
+        {
+            output_table.add_row(r.row("  Match", "⭐ matches --highlight-address", Color::Yellow));
+        }
+
+        if let Some(package) = &opts.package {
+            for edge in &package.edges {
+                if edge.parent == package.index && edge.output as usize == idx {
+                    output_table.add_row(r.row(
+                        "  Package",
+                        &format!("spent by tx #{}", edge.child),
+                        Color::Magenta,
+                    ));
+                }
+            }
+        }
+
+        if let Some(output_key) = taproot_output_key(output) {
+            output_table.add_row(r.row(
+                "  Taproot Output Key",
+                &output_key.to_string(),
+                Color::Cyan,
+            ));
+            if let Some(internal_key) = opts.internal_key {
+                let row = if taproot_tweak_matches(output_key, internal_key, opts.merkle_root) {
+                    r.row(
+                        "  Tweak Check",
+                        "✓ output key matches internal key + merkle root",
+                        Color::Green,
+                    )
+                } else {
+                    r.row(
+                        "  Tweak Check",
+                        "✗ output key does not match internal key + merkle root",
+                        Color::Red,
+                    )
+                };
+                output_table.add_row(row);
+            }
+        }
+
+        if opts.electrum {
+            output_table.add_row(r.row(
+                "  Electrum Scripthash",
+                &electrum_scripthash(output),
+                Color::Cyan,
+            ));
+        }
+
+        if let Some(hint) = lightning_output_hint(output) {
+            output_table.add_row(r.row(
+                "  Hint",
+                &format!("Possible Lightning: {} (heuristic)", hint.label()),
+                Color::Magenta,
+            ));
+        }
+
+        // Check if this is an ephemeral anchor (P2A)
+        if is_ephemeral_anchor(output) {
+            output_table.add_row(r.row(
+                "  Type",
+                "⚓ Ephemeral Anchor (P2A) - Pay-to-Anchor",
+                Color::Yellow,
+            ));
+            output_table.add_row(r.row(
+                "  Purpose",
+                "Anyone-can-spend anchor for CPFP fee bumping",
+                Color::Black,
+            ));
+        }
+        if opts.verbose {
+            output_table.add_row(r.row(
+                "  Script Type",
+                classify_output(output, opts),
+                Color::White,
+            ));
+            output_table.add_row(r.row("  Spendability", spendability(output), Color::White));
+        }
+        output_table.add_row(r.row(
+            "  Script Length",
+            &format!("{} bytes", output.script_pubkey.len()),
+            Color::White,
+        ));
+        let script_len = output.script_pubkey.len();
+        output_table.add_row(r.asm_row(
+            "  Script PubKey",
+            &truncate_middle(
+                &output.script_pubkey.to_asm_string(),
+                opts.truncate,
+                script_len,
+            ),
+        ));
+        output_table.add_row(r.row(
+            "  Script Hex",
+            &truncate_middle(
+                &hex::encode(output.script_pubkey.as_bytes()),
+                opts.truncate,
+                script_len,
+            ),
+            Color::Green,
+        ));
+        output_table.add_row(r.row(
+            "  Serialized Size",
+            &format!("{} bytes", encode::serialize(output).len()),
+            Color::White,
+        ));
+        output_table.add_row(r.row(
+            "  Virtual Size",
+            &format!("{} vBytes", output_vsize(output)),
+            Color::White,
+        ));
+
+        r.table(&output_table);
+        r.flush();
+    }
+    r.hidden_items(
+        tx.output.len(),
+        shown_outputs,
+        "output",
+        "--max-outputs-display",
+    );
+
+    if let Some((idx, runestone)) = tx_runestone(tx) {
+        render_runestone(r, tx, idx, &runestone);
+    }
+
+    // Summary
+    r.section("💰", "SUMMARY", None, Color::Yellow);
+
+    let mut summary = r.new_table();
+
+    summary.add_row(r.row(
+        "Total Output Value",
+        &format_total(total_output, opts.denomination, opts.thousands_sep),
+        Color::Yellow,
+    ));
+    let total_burned = checked_total(
+        tx.output
+            .iter()
+            .filter(|o| burn_reason(o).is_some())
+            .map(|o| o.value.to_sat()),
+    );
+    if total_burned != Some(0) {
+        summary.add_row(r.row(
+            "Burned Value",
+            &format!(
+                "🔥 {}",
+                format_total(total_burned, opts.denomination, opts.thousands_sep)
+            ),
+            Color::Red,
+        ));
+    }
+    if let Some(fee) = fee {
+        summary.add_row(r.row(
+            "Fee",
+            &format_amount_with_sats(fee, opts.denomination, opts.thousands_sep),
+            Color::Yellow,
+        ));
+
+        let fee_rate = fee as f64 / tx.vsize() as f64;
+        summary.add_row(r.row(
+            "Fee Rate",
+            &format!(
+                "{:.2} sat/vB, {}",
+                fee_rate,
+                fee_rate_bucket(fee_rate, &opts.fee_buckets)
+            ),
+            Color::White,
+        ));
+    }
+    let min_fee = min_relay_fee(tx, opts.min_relay_fee_rate);
+    summary.add_row(r.row(
+        "Min Relay Fee",
+        &format!("{} sats", format_number(min_fee, opts.thousands_sep)),
+        Color::White,
+    ));
+    if let Some(fee) = fee
+        && fee < min_fee
+    {
+        summary.add_row(r.row(
+            "Warning",
+            &format!(
+                "⚠ fee is {} sats below the minimum relay fee, the transaction won't propagate",
+                format_number(min_fee - fee, opts.thousands_sep)
+            ),
+            Color::Red,
+        ));
+    }
+    summary.add_row(r.row(
+        "Number of Inputs",
+        &tx.input.len().to_string(),
+        Color::White,
+    ));
+    summary.add_row(r.row(
+        "Number of Outputs",
+        &tx.output.len().to_string(),
+        Color::White,
+    ));
+    if let Some(change) = likely_change_output(tx) {
+        summary.add_row(r.row(
+            "Likely Change",
+            &format!("Output #{} (heuristic)", change),
+            Color::White,
+        ));
+    }
+
+    r.table(&summary);
+
+    if opts.histogram && !tx.output.is_empty() {
+        render_value_histogram(r, tx);
+    }
+
+    if opts.verbose {
+        render_replaceability(r, tx);
+        render_sighash_types(r, tx);
+    }
+
+    if !opts.quiet {
+        let footer = r.paint("═".repeat(70).cyan().bold());
+        r.line(&format!("\n{}\n", footer));
+    }
+}
+
+/// Compact table counting the outputs in each value bucket
+fn render_value_histogram(r: &mut Renderer, tx: &Transaction) {
+    let title = r.paint("Output Values".yellow().bold());
+    r.line(&format!("\n{}", title));
+
+    let mut table = r.new_table();
+    for (label, count) in value_histogram(tx) {
+        let percent = count * 100 / tx.output.len();
+        let color = if count > 0 {
+            Color::White
+        } else {
+            Color::BrightBlack
+        };
+        table.add_row(r.row(label, &format!("{} ({}%)", count, percent), color));
+    }
+    r.table(&table);
+}
+
+/// Color of each kind of field in the annotated hex
+fn field_color(kind: FieldKind) -> Color {
+    match kind {
+        FieldKind::Version => Color::Cyan,
+        FieldKind::Marker => Color::BrightBlack,
+        FieldKind::Count => Color::Yellow,
+        FieldKind::Outpoint => Color::Blue,
+        FieldKind::ScriptSig => Color::Green,
+        FieldKind::Sequence => Color::Magenta,
+        FieldKind::Value => Color::BrightYellow,
+        FieldKind::ScriptPubKey => Color::BrightGreen,
+        FieldKind::Witness => Color::BrightMagenta,
+        FieldKind::LockTime => Color::BrightCyan,
+    }
+}
+
+/// Print the raw serialization with every field color-coded and a legend below.
+/// Without color each field goes on its own line after a bracketed label.
+pub fn annotate_hex(tx: &Transaction, opts: &RenderOptions) -> String {
+    let mut r = Renderer::new(opts);
+    let bytes = encode::serialize(tx);
+    let fields = serialization_fields(tx);
+
+    r.section("🔬", "ANNOTATED HEX", None, Color::Cyan);
+    let mut start = 0;
+    if opts.color {
+        let mut line = String::new();
+        for field in &fields {
+            let hex = hex::encode(&bytes[start..field.end]);
+            line.push_str(&r.paint(hex.color(field_color(field.kind))));
+            start = field.end;
+        }
+        r.line(&line);
+        r.line("");
+        let legend: Vec<String> = FieldKind::ALL
+            .iter()
+            .filter(|kind| fields.iter().any(|field| field.kind == **kind))
+            .map(|kind| r.paint(format!("■ {}", kind.label()).color(field_color(*kind))))
+            .collect();
+        r.line(&legend.join("  "));
+    } else {
+        for field in &fields {
+            r.line(&format!(
+                "[{}] {}",
+                field.name,
+                hex::encode(&bytes[start..field.end])
+            ));
+            start = field.end;
+        }
+    }
+    r.out
+}
+
+/// Narrate the transaction in plain, uncolored English
+pub fn explain_transaction(tx: &Transaction, opts: &RenderOptions) -> String {
+    let total_output = total_output_value(tx);
+    let inputs = plural(tx.input.len(), "input");
+    let outputs = plural(tx.output.len(), "output");
+    let format_btc_total = |total: Option<u64>| {
+        total.map_or(TOTAL_OVERFLOW.to_string(), |sats| {
+            format_btc(sats, opts.thousands_sep)
+        })
+    };
+
+    let mut lines = vec![match opts.fee(tx) {
+        Some(fee) => format!(
+            "This transaction spends {} worth {} and creates {} totaling {}, paying a fee of {}.",
+            inputs,
+            format_btc_total(total_output.and_then(|total| total.checked_add(fee))),
+            outputs,
+            format_btc_total(total_output),
+            format_btc(fee, opts.thousands_sep)
+        ),
+        None => format!(
+            "This transaction spends {} and creates {} totaling {}.",
+            inputs,
+            outputs,
+            format_btc_total(total_output)
+        ),
+    }];
+
+    if tx.lock_time.to_consensus_u32() != 0 {
+        let lock_time = describe_lock_time(tx.lock_time);
+        lines.push(if is_lock_time_active(tx) {
+            format!(
+                "Its lock time of {} is enforced because at least one input has a non-final sequence.",
+                lock_time
+            )
+        } else {
+            format!(
+                "Its lock time of {} has no effect because every input sequence is final (0xffffffff).",
+                lock_time
+            )
+        });
+    }
+
+    if tx.is_coinbase() {
+        lines.push(
+            "It is a coinbase transaction: its input mints new coins instead of spending an existing output."
+                .to_string(),
+        );
+    } else {
+        for (idx, input) in tx.input.iter().enumerate() {
+            lines.push(format!(
+                "Input {} spends output {} of transaction {}. Its type is {}.",
+                idx,
+                input.previous_output.vout,
+                input.previous_output.txid,
+                detect_input_type(input)
+            ));
+        }
+    }
+
+    for (idx, output) in tx.output.iter().enumerate() {
+        let value = format_btc(output.value.to_sat(), opts.thousands_sep);
+        let sentence = if is_ephemeral_anchor(output) {
+            format!(
+                "Output {} is an ephemeral anchor holding {} for CPFP fee bumping.",
+                idx, value
+            )
+        } else if is_anyone_can_spend(output) {
+            format!(
+                "Output {} leaves {} spendable by anyone, without a signature.",
+                idx, value
+            )
+        } else if let Some(address) = output_address(output, opts.network) {
+            format!("Output {} sends {} to {}.", idx, value, address.address)
+        } else if let Some(address) = p2pk_address(output, opts.network) {
+            format!(
+                "Output {} sends {} to a bare public key (P2PK), known by its P2PKH address {}.",
+                idx, value, address
+            )
+        } else if output.script_pubkey.is_op_return() {
+            format!(
+                "Output {} is an OP_RETURN data carrier holding {}.",
+                idx, value
+            )
+        } else {
+            format!(
+                "Output {} locks {} to a script without a standard address.",
+                idx, value
+            )
+        };
+        lines.push(sentence);
+    }
+
+    lines.join("\n")
+}
+
+/// Show the content type, size and a text preview of each inscription
+fn render_inscriptions(r: &mut Renderer, inscriptions: &[(usize, Inscription)]) {
+    r.section(
+        "📜",
+        "INSCRIPTIONS",
+        Some(inscriptions.len()),
+        Color::Magenta,
+    );
+
+    for (idx, inscription) in inscriptions {
+        r.item_header("Input", *idx, Color::Magenta);
+
+        let mut table = r.new_table();
+        let content_type = inscription.content_type.as_deref().unwrap_or("unknown");
+        table.add_row(r.row("  Content Type", content_type, Color::Cyan));
+        table.add_row(r.row(
+            "  Content Size",
+            &format!("{} bytes", inscription.content.len()),
+            Color::White,
+        ));
+        if content_type.starts_with("text/") {
+            let text = String::from_utf8_lossy(&inscription.content);
+            let mut preview: String = text.chars().take(INSCRIPTION_PREVIEW_CHARS).collect();
+            if text.chars().count() > INSCRIPTION_PREVIEW_CHARS {
+                preview.push('…');
+            }
+            table.add_row(r.row("  Preview", &preview, Color::Green));
+        }
+        r.table(&table);
+    }
+}
+
+/// Runes etched, minted and transferred by the transaction's runestone
+fn render_runestone(r: &mut Renderer, tx: &Transaction, idx: usize, runestone: &Runestone) {
+    r.section("🪨", "RUNESTONE", None, Color::Magenta);
+    r.item_header("Output", idx, Color::Magenta);
+
+    let mut table = r.new_table();
+    match runestone.cenotaph {
+        Some(reason) => table.add_row(r.row(
+            "  Status",
+            &format!("⚠ Cenotaph: {}, runes sent to it are burned", reason),
+            Color::Red,
+        )),
+        None => table.add_row(r.row("  Status", "✓ Valid", Color::Green)),
+    };
+
+    if let Some(etching) = &runestone.etching {
+        let rune = etching.rune.as_deref().unwrap_or("(reserved name)");
+        table.add_row(r.row("  Etching", rune, Color::Cyan));
+        if let Some(symbol) = etching.symbol {
+            table.add_row(r.row("    Symbol", &symbol.to_string(), Color::White));
+        }
+        if let Some(divisibility) = etching.divisibility {
+            table.add_row(r.row("    Divisibility", &divisibility.to_string(), Color::White));
+        }
+        if let Some(premine) = etching.premine {
+            table.add_row(r.row("    Premine", &premine.to_string(), Color::White));
+        }
+        if let Some(terms) = &etching.terms {
+            let mut parts = Vec::new();
+            if let Some(cap) = terms.cap {
+                parts.push(format!("cap {}", cap));
+            }
+            if let Some(amount) = terms.amount {
+                parts.push(format!("amount {}", amount));
+            }
+            for (name, range) in [("height", terms.height), ("offset", terms.offset)] {
+                if range != (None, None) {
+                    let bound = |b: Option<u64>| b.map_or(String::new(), |b| b.to_string());
+                    parts.push(format!("{} {}..{}", name, bound(range.0), bound(range.1)));
+                }
+            }
+            table.add_row(r.row("    Mint Terms", &parts.join(", "), Color::White));
+        }
+        if etching.turbo {
+            table.add_row(r.row("    Turbo", "yes", Color::White));
+        }
+    }
+
+    if let Some(mint) = runestone.mint {
+        table.add_row(r.row("  Mint", &mint.to_string(), Color::Cyan));
+    }
+
+    for edict in &runestone.edicts {
+        let destination = if edict.output == tx.output.len() as u128 {
+            "split across all outputs".to_string()
+        } else {
+            format!("to output #{}", edict.output)
+        };
+        table.add_row(r.row(
+            "  Edict",
+            &format!("{} × {} {}", edict.amount, edict.id, destination),
+            Color::White,
+        ));
+    }
+
+    if let Some(pointer) = runestone.pointer {
+        table.add_row(r.row(
+            "  Pointer",
+            &format!("unallocated runes to output #{}", pointer),
+            Color::White,
+        ));
+    }
+    r.table(&table);
+}
+
+/// Summarize the spends between transactions of a batch, e.g. for CPFP chains
+pub fn render_package_relationships(edges: &[PackageEdge], opts: &RenderOptions) -> String {
+    let mut r = Renderer::new(opts);
+    r.section(
+        "🔗",
+        "PACKAGE RELATIONSHIPS",
+        Some(edges.len()),
+        Color::Magenta,
+    );
+
+    let mut table = r.new_table();
+    for edge in edges {
+        table.add_row(r.row(
+            &format!("tx #{}", edge.child),
+            &format!(
+                "input {} spends output {} of tx #{}",
+                edge.input, edge.output, edge.parent
+            ),
+            Color::White,
+        ));
+    }
+    r.table(&table);
+    r.out
+}
+
+/// List the sighash types of each signing input and flag unusual combinations
+fn render_sighash_types(r: &mut Renderer, tx: &Transaction) {
+    let per_input: Vec<Vec<EcdsaSighashType>> = tx.input.iter().map(input_sighash_types).collect();
+    if per_input.iter().all(Vec::is_empty) {
+        return;
+    }
+
+    r.section("✍️", "SIGHASH TYPES", None, Color::Cyan);
+
+    let mut table = r.new_table();
+    for (idx, types) in per_input.iter().enumerate() {
+        if types.is_empty() {
+            continue;
+        }
+        let names: Vec<String> = types.iter().map(ToString::to_string).collect();
+        table.add_row(r.row(
+            &format!("  Input #{}", idx),
+            &names.join(", "),
+            Color::White,
+        ));
+    }
+    for warning in sighash_warnings(tx, &per_input) {
+        table.add_row(r.row("Warning", &format!("⚠ {}", warning), Color::Red));
+    }
+
+    r.table(&table);
+}
+
+/// Render the outpoints spent by several transactions of a batch
+pub fn render_conflicts(conflicts: &[Conflict], opts: &RenderOptions) -> String {
+    let mut r = Renderer::new(opts);
+    r.section("⚔️", "CONFLICTS", Some(conflicts.len()), Color::Red);
+
+    let mut table = r.new_table();
+    for conflict in conflicts {
+        let spenders: Vec<String> = conflict
+            .spenders
+            .iter()
+            .map(|idx| format!("tx #{}", idx))
+            .collect();
+        let (last, rest) = spenders
+            .split_last()
+            .expect("conflicts have several spenders");
+        let verb = if rest.len() == 1 { "both" } else { "all" };
+        table.add_row(r.row(
+            "⚠ CONFLICT",
+            &format!(
+                "{} and {} {} spend {}",
+                rest.join(", "),
+                last,
+                verb,
+                conflict.outpoint
+            ),
+            Color::Red,
+        ));
+    }
+    r.table(&table);
+    r.out
+}
+
+/// One dense table with a row of key facts per transaction, for scanning many
+/// transactions at once
+pub fn render_summary_table<'a>(
+    txs: impl IntoIterator<Item = (usize, &'a Transaction)>,
+    opts: &RenderOptions,
+) -> String {
+    let mut r = Renderer::new(opts);
+    r.section("📋", "SUMMARY TABLE", None, Color::Cyan);
+
+    let mut table = r.new_table();
+    let titles = [
+        "#",
+        "Txid",
+        "In",
+        "Out",
+        "Total Value",
+        "Fee",
+        "Size / vSize / Weight",
+        "Types",
+    ];
+    table.set_titles(Row::new(
+        titles
+            .iter()
+            .map(|title| r.styled_cell(title, |t| t.bold()))
+            .collect(),
+    ));
+    for (idx, tx) in txs {
+        let total_output = total_output_value(tx).map_or(TOTAL_OVERFLOW.to_string(), |sats| {
+            format_amount(sats, opts.denomination, opts.thousands_sep)
+        });
+        let fee = opts.fee(tx).map_or("unknown".to_string(), |fee| {
+            format!("{} sats", format_number(fee, opts.thousands_sep))
+        });
+        table.add_row(Row::new(vec![
+            r.cell(&idx.to_string(), Color::Yellow),
+            r.cell(&tx.compute_txid().to_string(), Color::Green),
+            r.cell(&tx.input.len().to_string(), Color::White),
+            r.cell(&tx.output.len().to_string(), Color::White),
+            r.cell(&total_output, Color::Yellow),
+            r.cell(&fee, Color::Yellow),
+            r.cell(
+                &format!(
+                    "{} B / {} vB / {} WU",
+                    tx.total_size(),
+                    tx.vsize(),
+                    tx.weight().to_wu()
+                ),
+                Color::White,
+            ),
+            r.cell(&type_summary(tx, opts), Color::Cyan),
+        ]));
+    }
+    r.table(&table);
+    r.out
+}
+
+/// Explain input by input why the transaction is or isn't replaceable
+fn render_replaceability(r: &mut Renderer, tx: &Transaction) {
+    r.section("🔁", "REPLACEABILITY", None, Color::Cyan);
+
+    let mut table = r.new_table();
+
+    table.add_row(r.row(
+        "Rule",
+        "BIP125: replaceable if any input sequence < 0xfffffffe",
+        Color::Black,
+    ));
+    table.add_row(r.row(
+        "Node Policy",
+        if r.opts.full_rbf {
+            "full-RBF: any transaction may be replaced (Bitcoin Core 28+ default)"
+        } else {
+            "opt-in only (pass --full-rbf to assume full-RBF nodes)"
+        },
+        Color::Black,
+    ));
+
+    for (idx, input) in tx.input.iter().enumerate() {
+        let verdict = if input.sequence.is_rbf() {
+            "< 0xfffffffe, signals replaceability"
+        } else {
+            ">= 0xfffffffe, does not signal"
+        };
+        table.add_row(r.row(
+            &format!("  Input #{}", idx),
+            &format!("{:#010x} ({})", input.sequence.0, verdict),
+            Color::White,
+        ));
+    }
+
+    table.add_row(r.row(
+        "Verdict",
+        replaceability(tx, r.opts.full_rbf),
+        Color::Yellow,
+    ));
+
+    r.table(&table);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // P2WPKH segwit transaction with 1 input and 3 outputs
+    const SEGWIT_TX_HEX: &str = "020000000001010eeb61beeddeaab8a7bb024efcac1fa3faecb7c96c4127782e6bc7cd59fc51490200000000fffffffd03afd701000000000017a914715a091837e1340c8f4d11c20a16a4c92cee9af187ce22000000000000225120a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208aef780800000000001600148db324a5c4bf820717091087769dee302809ccb202483045022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f3140220283339f2abbd165bbc6b4b305db28b9a064f051c2b097f318c2fa507bf320bc3012103cc976f202ab9e2d0bbe3ed8e728aadd6042294f223ff665516c114733647b6ba00000000";
+
+    #[test]
+    fn test_p2a_address_follows_network() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::from_hex("51024e73").unwrap();
+        tx.output[0].value = bitcoin::Amount::ZERO;
+
+        for (network, address) in [
+            (bitcoin::Network::Bitcoin, "bc1pfeessrawgf"),
+            (bitcoin::Network::Testnet, "tb1pfees9rn5nz"),
+            (bitcoin::Network::Regtest, "bcrt1pfeesnyr2tx"),
+        ] {
+            let opts = RenderOptions::default().color(false).network(network);
+            let rendered = render_transaction(&tx, &opts);
+            assert!(rendered.contains("Ephemeral Anchor (P2A)"));
+            assert_eq!(rendered.matches(address).count(), 1, "{}", rendered);
+            assert_eq!(rendered.matches("  Address").count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_merkle_depth_at_control_block_limit() {
+        // Script path spend of `OP_TRUE` with a control block holding `depth` nodes
+        let spend = |depth: usize| {
+            let mut control_block = vec![0xc0];
+            control_block.extend(
+                hex::decode("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                    .unwrap(),
+            );
+            control_block.extend(vec![0x11; 32 * depth]);
+            bitcoin::Witness::from_slice(&[vec![0x51], control_block])
+        };
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default().color(false);
+
+        tx.input[0].witness = spend(128);
+        let rendered = render_transaction(&tx, &opts);
+        assert!(
+            rendered.contains("128 levels (script tree with up to 2^128 leaves)"),
+            "{}",
+            rendered
+        );
+
+        tx.input[0].witness = spend(127);
+        assert!(
+            render_transaction(&tx, &opts).contains(&format!("with up to {} leaves", 1u128 << 127))
+        );
+    }
+
+    #[test]
+    fn test_asm_token_color() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let asm = tx.output[0].script_pubkey.to_asm_string();
+        let colors: Vec<Color> = asm.split(' ').map(asm_token_color).collect();
+        assert_eq!(
+            colors,
+            [
+                Color::Magenta,
+                Color::Magenta,
+                Color::Yellow,
+                Color::Magenta
+            ]
+        );
+
+        assert_eq!(asm_token_color("0102"), Color::Green);
+        assert_eq!(asm_token_color("0102…0304"), Color::Green);
+        assert_eq!(asm_token_color("…"), Color::White);
+        assert_eq!(asm_token_color("(72"), Color::White);
+
+        // Without color the script reads exactly like the plain ASM
+        let opts = RenderOptions::default().color(false);
+        assert!(render_transaction(&tx, &opts).contains(&format!("Script PubKey    {} \n", asm)));
+    }
+
+    #[test]
+    fn test_unsigned_input() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default().color(false);
+        assert!(render_transaction(&tx, &opts).contains("Signed Inputs          1/1 \n"));
+
+        let mut unsigned = tx.input[0].clone();
+        unsigned.witness.clear();
+        unsigned.previous_output.vout = 0;
+        tx.input.push(unsigned);
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("Signed Inputs          1/2 ⚠ 1 unsigned"));
+        assert_eq!(
+            rendered
+                .matches("⚠ input not signed (empty scriptSig and witness)")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_confirmation_status() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default().color(false);
+        assert!(!render_transaction(&tx, &opts).contains("Status"));
+
+        let confirmed = opts.clone().confirmation(Some(Confirmation::Confirmed {
+            height: 840_000,
+            time: 1_713_571_767,
+        }));
+        assert!(
+            render_transaction(&tx, &confirmed)
+                .contains("✓ Confirmed in block 840,000, block time 1713571767 (Unix time)")
+        );
+        let unconfirmed = opts.confirmation(Some(Confirmation::Unconfirmed));
+        assert!(render_transaction(&tx, &unconfirmed).contains("⏳ Unconfirmed"));
+    }
+
+    #[test]
+    fn test_render_summary_table() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default()
+            .color(false)
+            .prevout_values(vec![700_000]);
+        let table = render_summary_table([(0, &tx), (1, &tx)], &opts);
+        assert_eq!(table.matches(&tx.compute_txid().to_string()).count(), 2);
+        assert!(table.contains("15,092 sats"));
+        assert!(table.contains("267 B / 185 vB / 738 WU"));
+        assert!(table.contains("1×P2WPKH → 1×P2SH, 1×P2TR, 1×P2WPKH"));
+        // Nothing from the full report
+        assert!(!table.contains("INPUTS"));
+    }
+
+    #[test]
+    fn test_max_outputs_display() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default()
+            .color(false)
+            .max_outputs_display(Some(1));
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("Output #0"));
+        assert!(!rendered.contains("Output #1"));
+        assert!(
+            rendered
+                .contains("... and 2 more outputs (increase --max-outputs-display to see them)")
+        );
+        assert!(rendered.contains("Number of Outputs   3"));
+
+        // A limit above the count hides nothing
+        let opts = opts
+            .max_outputs_display(Some(3))
+            .max_inputs_display(Some(1));
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("Output #2"));
+        assert!(!rendered.contains("more"));
+    }
+
+    #[test]
+    fn test_output_order() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(output_display_order(&tx, OutputOrder::None), [0, 1, 2]);
+        assert_eq!(output_display_order(&tx, OutputOrder::ValueAsc), [1, 0, 2]);
+        assert_eq!(output_display_order(&tx, OutputOrder::ValueDesc), [2, 0, 1]);
+
+        let opts = RenderOptions::default()
+            .color(false)
+            .output_order(OutputOrder::ValueDesc)
+            .max_outputs_display(Some(2));
+        let rendered = render_transaction(&tx, &opts);
+        let first = rendered.find("Output #2 (display position 1)").unwrap();
+        let second = rendered.find("Output #0 (display position 2)").unwrap();
+        assert!(first < second);
+        assert!(!rendered.contains("Output #1"));
+        // The transaction itself is untouched
+        assert!(rendered.contains(&tx.compute_txid().to_string()));
+        assert!(rendered.contains("Likely Change       Output #2"));
+
+        assert!(
+            !render_transaction(&tx, &RenderOptions::default().color(false))
+                .contains("display position")
+        );
+    }
+
+    #[test]
+    fn test_annotate_hex() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let plain = annotate_hex(&tx, &RenderOptions::default().color(false));
+        assert!(
+            plain.contains("[version] 02000000\n[SegWit marker and flag] 0001\n[input count] 01\n")
+        );
+        assert!(plain.contains("[output 2 value] ef78080000000000\n"));
+        assert!(plain.ends_with("[lock time] 00000000\n"));
+        // Every byte is shown exactly once
+        let hex: String = plain
+            .lines()
+            .filter_map(|line| line.strip_prefix('[')?.split_once("] "))
+            .map(|(_, hex)| hex)
+            .collect();
+        assert_eq!(hex, SEGWIT_TX_HEX);
+
+        let colored = annotate_hex(&tx, &RenderOptions::default());
+        assert!(colored.contains("■ witness"));
+        assert!(!colored.contains("[version]"));
+    }
+
+    #[test]
+    fn test_output_address_round_trips_p2wpkh() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let address = output_address(&tx.output[2], bitcoin::Network::Bitcoin).unwrap();
+        assert_eq!(
+            address.address.to_string(),
+            "bc1q3kejffwyh7pqw9cfzzrhd80wxq5qnn9jvem8v9"
+        );
+        assert!(address.round_trips);
+        assert!(!format_address(&address).contains("round-trip"));
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("00112233", None, 4), "00112233");
+        assert_eq!(truncate_middle("00112233", Some(8), 4), "00112233");
+        assert_eq!(
+            truncate_middle("0011223344", Some(4), 5),
+            "00…44 (5 bytes total)"
+        );
+        assert_eq!(
+            truncate_middle("0011223344", Some(5), 5),
+            "001…44 (5 bytes total)"
+        );
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(
+            format_amount_with_sats(120751, Denomination::Btc, false),
+            "0.00120751 BTC (120751 satoshis)"
+        );
+        assert_eq!(
+            format_amount_with_sats(120751, Denomination::Mbtc, false),
+            "1.20751 mBTC (120751 satoshis)"
+        );
+        assert_eq!(
+            format_amount_with_sats(120751, Denomination::Bits, false),
+            "1207.51 bits (120751 satoshis)"
+        );
+        assert_eq!(
+            format_amount_with_sats(120751, Denomination::Sat, false),
+            "120751 satoshis"
+        );
+        assert_eq!(
+            format_amount_with_sats(120751, Denomination::Btc, true),
+            "0.00120751 BTC (120,751 satoshis)"
+        );
+        assert_eq!("mBTC".parse(), Ok(Denomination::Mbtc));
+    }
+
+    #[test]
+    fn test_format_btc_is_exact() {
+        assert_eq!(format_btc(0, true), "0.00000000 BTC");
+        assert_eq!(format_btc(1, true), "0.00000001 BTC");
+        assert_eq!(format_btc(100_000_000, true), "1.00000000 BTC");
+        // Close to the supply cap, where f64 loses the last digits
+        assert_eq!(
+            format_btc(2_099_999_997_690_000, true),
+            "20,999,999.97690000 BTC"
+        );
+        assert_eq!(
+            format_btc(2_099_999_997_690_001, false),
+            "20999999.97690001 BTC"
+        );
+        assert!("ubtc".parse::<Denomination>().is_err());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0, true), "0");
+        assert_eq!(format_number(999, true), "999");
+        assert_eq!(format_number(1_000, true), "1,000");
+        assert_eq!(format_number(100_000_000, true), "100,000,000");
+        assert_eq!(format_number(u64::MAX, true), "18,446,744,073,709,551,615");
+        assert_eq!(format_number(100_000_000, false), "100000000");
+    }
+
+    #[test]
+    fn test_witness_preview() {
+        let item = [0xab; 72];
+        let opts = RenderOptions {
+            max_witness_preview: Some(4),
+            ..RenderOptions::default()
+        };
+        assert_eq!(witness_preview(&item, &opts), "abab…abab (72 bytes total)");
+        // Short items are shown in full
+        assert_eq!(witness_preview(&item[..4], &opts), "abababab");
+
+        let full = RenderOptions {
+            max_witness_preview: None,
+            ..RenderOptions::default()
+        };
+        assert_eq!(witness_preview(&item, &full), "ab".repeat(72));
+    }
+
+    #[test]
+    fn test_value_style() {
+        let mut output = decode_transaction(SEGWIT_TX_HEX).unwrap().output[2].clone();
+        assert_eq!(value_style(&output)("v").fgcolor, Some(Color::Yellow));
+
+        output.value = bitcoin::Amount::from_sat(100);
+        assert_eq!(value_style(&output)("v").fgcolor, Some(Color::BrightBlack));
+
+        output.value = bitcoin::Amount::from_sat(150_000_000);
+        let styled = value_style(&output)("v");
+        assert_eq!(styled.fgcolor, Some(Color::BrightYellow));
+        assert!(styled.style.contains(colored::Styles::Bold));
+    }
+
+    #[test]
+    fn test_value_bar() {
+        assert_eq!(value_bar(100, 100, 5), "▇▇▇▇▇");
+        assert_eq!(value_bar(50, 100, 4), "▇▇░░");
+        assert_eq!(value_bar(0, 100, 3), "░░░");
+        assert_eq!(value_bar(0, 0, 3), "░░░");
+        assert_eq!(value_bar(u64::MAX, u64::MAX, 2), "▇▇");
+
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions::default().color(false).value_bars(Some(20));
+        let rendered = render_transaction(&tx, &opts);
+        // The largest output fills the bar
+        assert!(rendered.contains(&format!("(555,247 satoshis)  {}", "▇".repeat(20))));
+        assert!(rendered.contains("(8,910 satoshis)  ░░░░░░░░░░░░░░░░░░░░"));
+    }
+
+    #[test]
+    fn test_check_strict_der() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let sig = tx.input[0].witness.nth(0).unwrap().to_vec();
+        assert_eq!(check_strict_der(&sig), Ok(()));
+        assert_eq!(decode_witness_item(&sig), "Signature (DER, strict ✓)");
+
+        // S replaced by n - S, which verifies the same but is high
+        let high_s = hex::decode("3046022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f314022100d7ccc60d5442e9a44394b4cfa24d7464b45fd7ca843f210a33a2b9851104357e01").unwrap();
+        assert_eq!(check_strict_der(&high_s), Err("high S"));
+        assert_eq!(decode_witness_item(&high_s), "⚠ non-strict DER (high S)");
+
+        let mut bad_length = sig.clone();
+        bad_length[1] += 1;
+        assert_eq!(
+            check_strict_der(&bad_length),
+            Err("sequence length doesn't match")
+        );
+
+        // R 0x00 0x7f.. has an unnecessary leading zero
+        let padded = hex::decode("3007020200010201010a").unwrap();
+        assert_eq!(check_strict_der(&padded), Err("integer has excess padding"));
+        let negative = hex::decode("30060201810201010a").unwrap();
+        assert_eq!(check_strict_der(&negative), Err("negative integer"));
+
+        assert_eq!(decode_witness_item(&[0x11; 64]), "Signature (Schnorr)");
+        assert_eq!(decode_witness_item(&[0x04; 65]), "Public Key");
+
+        // Schnorr signatures starting with the DER sequence tag aren't checked as DER
+        let mut tx = tx;
+        for len in [64, 65] {
+            let mut schnorr = vec![0x11; len];
+            schnorr[0] = 0x30;
+            assert_eq!(decode_witness_item(&schnorr), "Signature (Schnorr)");
+            tx.input[0].witness = bitcoin::Witness::from_slice(&[schnorr]);
+            let rendered = render_transaction(&tx, &RenderOptions::default().color(false));
+            assert!(!rendered.contains("non-strict DER"), "{}", rendered);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Minimal Esplora REST client for fetching raw transactions and their confirmation status
+
+use bitcoin::Txid;
+use bitcoin_tx_decoder::Confirmation;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// GET `{base_url}{path}` and return the response body
+fn get(base_url: &str, path: &str, txid: &Txid) -> Result<String, String> {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    match ureq::get(&url).call() {
+        Ok(response) => response
+            .into_string()
+            .map_err(|e| format!("Failed to read Esplora response from {}: {}", url, e)),
+        Err(ureq::Error::Status(404, _)) => Err(format!("No such transaction {}", txid)),
+        Err(ureq::Error::Status(code, _)) => {
+            Err(format!("Esplora returned HTTP {} for {}", code, url))
+        }
+        Err(ureq::Error::Transport(e)) => {
+            Err(format!("Esplora unreachable at {}: {}", base_url, e))
+        }
+    }
+}
+
+/// Parse the txid given on the command line
+fn parse_txid(txid: &str) -> Result<Txid, String> {
+    Txid::from_str(txid).map_err(|e| format!("Invalid txid {}: {}", txid, e))
+}
+
+/// Fetch the hex-encoded transaction with the given txid via `/tx/{txid}/hex`
+pub fn get_raw_transaction(base_url: &str, txid: &str) -> Result<String, String> {
+    let txid = parse_txid(txid)?;
+    let hex = get(base_url, &format!("/tx/{}/hex", txid), &txid)?;
+    Ok(hex.trim().to_string())
+}
+
+/// Fetch whether the transaction is confirmed via `/tx/{txid}/status`
+pub fn get_confirmation(base_url: &str, txid: &str) -> Result<Confirmation, String> {
+    let txid = parse_txid(txid)?;
+    let body = get(base_url, &format!("/tx/{}/status", txid), &txid)?;
+    let status: Value = serde_json::from_str(&body)
+        .map_err(|_| "Esplora returned a malformed status".to_string())?;
+    parse_status(&status).ok_or_else(|| "Esplora returned a malformed status".to_string())
+}
+
+/// Read `{"confirmed": true, "block_height": .., "block_time": ..}`
+fn parse_status(status: &Value) -> Option<Confirmation> {
+    if !status["confirmed"].as_bool()? {
+        return Some(Confirmation::Unconfirmed);
+    }
+    Some(Confirmation::Confirmed {
+        height: status["block_height"].as_u64()?,
+        time: status["block_time"].as_u64()?,
+    })
+}
@@ -0,0 +1,5005 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Decode Bitcoin transactions and render human-readable reports about them
+//!
+//! No input can make the public API panic: malformed data is reported as an `Err`, and
+//! the renderers handle any transaction that decodes, however unusual its scripts and
+//! witnesses. `tests/fuzz.rs` checks this with generated inputs.
+
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{EcdsaSighashType, TapSighashType};
+use bitcoin::{Network, Psbt, Transaction, consensus::encode};
+use log::{debug, info};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+mod display;
+
+pub use display::{
+    DEFAULT_MAX_WITNESS_PREVIEW, TableStyle, annotate_hex, explain_transaction, render_conflicts,
+    render_package_relationships, render_summary_table, render_transaction, write_transaction,
+};
+
+/// Display preferences for [`render_transaction`] and [`explain_transaction`]
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Network used to derive output addresses
+    pub network: Network,
+    /// Emit ANSI colors (still subject to terminal detection)
+    pub color: bool,
+    /// Include the additional explanatory sections
+    pub verbose: bool,
+    /// Values in satoshis of the outputs spent by each input, used to compute the fee
+    pub prevout_values: Vec<u64>,
+    /// Thresholds used to label the fee rate
+    pub fee_buckets: FeeBuckets,
+    /// Also show the txid in internal (little-endian) byte order
+    pub show_internal_hash: bool,
+    /// Shorten hex and ASM cells longer than this many characters
+    pub truncate: Option<usize>,
+    /// Show the Electrum scripthash of every output
+    pub electrum: bool,
+    /// Unit for output values and summary totals
+    pub denomination: Denomination,
+    /// Border style used for every table
+    pub table_style: TableStyle,
+    /// Mark outputs paying to this address
+    pub highlight_address: Option<String>,
+    /// Show only the first and last N/2 bytes of longer witness items
+    pub max_witness_preview: Option<usize>,
+    /// Leave out emojis and separator lines around section titles
+    pub quiet: bool,
+    /// Position in a decoded batch, used to annotate spends between its transactions
+    pub package: Option<PackageContext>,
+    /// Minimum relay fee rate in sat/kvB, used to compute the minimum relay fee
+    pub min_relay_fee_rate: u64,
+    /// Per-input amounts from a PSBT, `None` where the PSBT carries no UTXO for the input
+    pub input_amounts: Vec<Option<u64>>,
+    /// Draw a bar this many characters wide next to each output value
+    pub value_bars: Option<usize>,
+    /// Output spent by each input, `None` where it isn't known
+    pub spent_outputs: Vec<Option<bitcoin::TxOut>>,
+    /// Internal key to check P2TR output keys against
+    pub internal_key: Option<bitcoin::key::UntweakedPublicKey>,
+    /// Script tree merkle root committed to with `internal_key`, `None` for key-path only
+    pub merkle_root: Option<bitcoin::TapNodeHash>,
+    /// Txid the transaction is expected to have
+    pub expected_txid: Option<bitcoin::Txid>,
+    /// Wtxid the transaction is expected to have, used to detect witness malleation
+    pub expected_wtxid: Option<bitcoin::Wtxid>,
+    /// Group the digits of satoshi counts and whole BTC, e.g. `100,000,000`
+    pub thousands_sep: bool,
+    /// Block height a coinbase transaction is expected to commit to (BIP34)
+    pub expected_height: Option<u64>,
+    /// Assume nodes replace transactions whether or not they signal BIP125
+    pub full_rbf: bool,
+    /// Render only the first N inputs; the summary still counts all of them
+    pub max_inputs_display: Option<usize>,
+    /// Render only the first N outputs; the summary still counts all of them
+    pub max_outputs_display: Option<usize>,
+    /// Order outputs are rendered in, keeping their original indexes
+    pub output_order: OutputOrder,
+    /// Custom script templates, tried before the built-in ones by [`classify_output`]
+    pub script_patterns: Vec<ScriptPattern>,
+    /// Add a histogram of output values to the summary
+    pub histogram: bool,
+    /// Whether the transaction is confirmed, as reported by a block explorer
+    pub confirmation: Option<Confirmation>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            network: Network::Bitcoin,
+            color: true,
+            verbose: false,
+            prevout_values: Vec::new(),
+            fee_buckets: FeeBuckets::default(),
+            show_internal_hash: false,
+            truncate: None,
+            electrum: false,
+            denomination: Denomination::Btc,
+            table_style: TableStyle::Clean,
+            highlight_address: None,
+            max_witness_preview: Some(DEFAULT_MAX_WITNESS_PREVIEW),
+            quiet: false,
+            package: None,
+            min_relay_fee_rate: DEFAULT_MIN_RELAY_FEE_RATE,
+            input_amounts: Vec::new(),
+            value_bars: None,
+            spent_outputs: Vec::new(),
+            internal_key: None,
+            merkle_root: None,
+            expected_txid: None,
+            expected_wtxid: None,
+            thousands_sep: true,
+            expected_height: None,
+            full_rbf: false,
+            max_inputs_display: None,
+            max_outputs_display: None,
+            output_order: OutputOrder::default(),
+            script_patterns: Vec::new(),
+            histogram: false,
+            confirmation: None,
+        }
+    }
+}
+
+/// Builder-style setters, e.g. `RenderOptions::default().network(..).color(false)`
+impl RenderOptions {
+    /// Network used to derive output addresses
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Emit ANSI colors
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Include the additional explanatory sections
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Values in satoshis of the outputs spent by each input
+    pub fn prevout_values(mut self, prevout_values: Vec<u64>) -> Self {
+        self.prevout_values = prevout_values;
+        self
+    }
+
+    /// Thresholds used to label the fee rate
+    pub fn fee_buckets(mut self, fee_buckets: FeeBuckets) -> Self {
+        self.fee_buckets = fee_buckets;
+        self
+    }
+
+    /// Also show the txid in internal byte order
+    pub fn show_internal_hash(mut self, show_internal_hash: bool) -> Self {
+        self.show_internal_hash = show_internal_hash;
+        self
+    }
+
+    /// Shorten hex and ASM cells longer than this many characters
+    pub fn truncate(mut self, truncate: Option<usize>) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Show the Electrum scripthash of every output
+    pub fn electrum(mut self, electrum: bool) -> Self {
+        self.electrum = electrum;
+        self
+    }
+
+    /// Unit for output values and summary totals
+    pub fn denomination(mut self, denomination: Denomination) -> Self {
+        self.denomination = denomination;
+        self
+    }
+
+    /// Border style used for every table
+    pub fn table_style(mut self, table_style: TableStyle) -> Self {
+        self.table_style = table_style;
+        self
+    }
+
+    /// Mark outputs paying to this address
+    pub fn highlight_address(mut self, highlight_address: Option<String>) -> Self {
+        self.highlight_address = highlight_address;
+        self
+    }
+
+    /// Shorten witness items longer than this many bytes, `None` shows them in full
+    pub fn max_witness_preview(mut self, max_witness_preview: Option<usize>) -> Self {
+        self.max_witness_preview = max_witness_preview;
+        self
+    }
+
+    /// Leave out emojis and separator lines around section titles
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Position in a decoded batch
+    pub fn package(mut self, package: Option<PackageContext>) -> Self {
+        self.package = package;
+        self
+    }
+
+    /// Minimum relay fee rate in sat/kvB
+    pub fn min_relay_fee_rate(mut self, min_relay_fee_rate: u64) -> Self {
+        self.min_relay_fee_rate = min_relay_fee_rate;
+        self
+    }
+
+    /// Per-input amounts from a PSBT
+    pub fn input_amounts(mut self, input_amounts: Vec<Option<u64>>) -> Self {
+        self.input_amounts = input_amounts;
+        self
+    }
+
+    /// Width of the bar drawn next to each output value
+    pub fn value_bars(mut self, value_bars: Option<usize>) -> Self {
+        self.value_bars = value_bars;
+        self
+    }
+
+    /// Output spent by each input
+    pub fn spent_outputs(mut self, spent_outputs: Vec<Option<bitcoin::TxOut>>) -> Self {
+        self.spent_outputs = spent_outputs;
+        self
+    }
+
+    /// Internal key to check P2TR output keys against
+    pub fn internal_key(mut self, internal_key: Option<bitcoin::key::UntweakedPublicKey>) -> Self {
+        self.internal_key = internal_key;
+        self
+    }
+
+    /// Script tree merkle root committed to with the internal key
+    pub fn merkle_root(mut self, merkle_root: Option<bitcoin::TapNodeHash>) -> Self {
+        self.merkle_root = merkle_root;
+        self
+    }
+
+    /// Txid the transaction is expected to have
+    pub fn expected_txid(mut self, expected_txid: Option<bitcoin::Txid>) -> Self {
+        self.expected_txid = expected_txid;
+        self
+    }
+
+    /// Wtxid the transaction is expected to have
+    pub fn expected_wtxid(mut self, expected_wtxid: Option<bitcoin::Wtxid>) -> Self {
+        self.expected_wtxid = expected_wtxid;
+        self
+    }
+
+    /// Whether to group digits with thousands separators
+    pub fn thousands_sep(mut self, thousands_sep: bool) -> Self {
+        self.thousands_sep = thousands_sep;
+        self
+    }
+
+    /// Block height a coinbase transaction is expected to commit to
+    pub fn expected_height(mut self, expected_height: Option<u64>) -> Self {
+        self.expected_height = expected_height;
+        self
+    }
+
+    /// Whether to assume a full-RBF node policy
+    pub fn full_rbf(mut self, full_rbf: bool) -> Self {
+        self.full_rbf = full_rbf;
+        self
+    }
+
+    /// Limit the number of inputs rendered
+    pub fn max_inputs_display(mut self, max_inputs_display: Option<usize>) -> Self {
+        self.max_inputs_display = max_inputs_display;
+        self
+    }
+
+    /// Limit the number of outputs rendered
+    pub fn max_outputs_display(mut self, max_outputs_display: Option<usize>) -> Self {
+        self.max_outputs_display = max_outputs_display;
+        self
+    }
+
+    /// Order to render outputs in
+    pub fn output_order(mut self, output_order: OutputOrder) -> Self {
+        self.output_order = output_order;
+        self
+    }
+
+    /// Whether to add a histogram of output values to the summary
+    pub fn histogram(mut self, histogram: bool) -> Self {
+        self.histogram = histogram;
+        self
+    }
+
+    /// Confirmation status to show in the overview
+    pub fn confirmation(mut self, confirmation: Option<Confirmation>) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// Recognize outputs matched by `matches` as `name`, e.g. for a protocol's own
+    /// OP_RETURN format. Patterns are tried in the order they were registered.
+    pub fn register_pattern(
+        mut self,
+        name: &'static str,
+        matches: fn(&bitcoin::TxOut) -> bool,
+    ) -> Self {
+        self.script_patterns.push(ScriptPattern { name, matches });
+        self
+    }
+
+    /// Fee paid by the transaction, if prevout values were supplied and are consistent
+    fn fee(&self, tx: &Transaction) -> Option<u64> {
+        if self.prevout_values.is_empty() {
+            return None;
+        }
+        compute_fee(tx, &self.prevout_values).ok()
+    }
+}
+
+/// Bitcoin Core's default `-minrelaytxfee`, in sat/kvB
+pub const DEFAULT_MIN_RELAY_FEE_RATE: u64 = 1_000;
+
+/// Minimum fee in satoshis the transaction must pay to be relayed at the given rate,
+/// rounded up like Bitcoin Core's `CFeeRate::GetFee`. Absurd rates saturate at `u64::MAX`.
+pub fn min_relay_fee(tx: &Transaction, min_relay_fee_rate_sat_per_kvb: u64) -> u64 {
+    let fee = (tx.vsize() as u128 * min_relay_fee_rate_sat_per_kvb as u128).div_ceil(1_000);
+    u64::try_from(fee).unwrap_or(u64::MAX)
+}
+
+/// Fee-rate thresholds (sat/vB) used to label how quickly a transaction may confirm
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeBuckets {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl Default for FeeBuckets {
+    fn default() -> Self {
+        FeeBuckets {
+            low: 5.0,
+            medium: 20.0,
+            high: 50.0,
+        }
+    }
+}
+
+impl FromStr for FeeBuckets {
+    type Err = String;
+
+    /// Parse `LOW:MED:HIGH` thresholds, e.g. `5:20:50`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bounds = s
+            .split(':')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid fee rate: {}", e))?;
+
+        match bounds.as_slice() {
+            [low, medium, high] if 0.0 < *low && low < medium && medium < high => Ok(FeeBuckets {
+                low: *low,
+                medium: *medium,
+                high: *high,
+            }),
+            [_, _, _] => Err("thresholds must be positive and strictly increasing".to_string()),
+            _ => Err("expected three thresholds as LOW:MED:HIGH".to_string()),
+        }
+    }
+}
+
+/// Unit used to display output values and summary totals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Denomination {
+    #[default]
+    Btc,
+    Mbtc,
+    Bits,
+    Sat,
+}
+
+impl FromStr for Denomination {
+    type Err = String;
+
+    /// Parse `btc`, `mbtc`, `bits` or `sat`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "btc" => Ok(Denomination::Btc),
+            "mbtc" => Ok(Denomination::Mbtc),
+            "bits" => Ok(Denomination::Bits),
+            "sat" | "sats" => Ok(Denomination::Sat),
+            _ => Err("expected one of btc, mbtc, bits, sat".to_string()),
+        }
+    }
+}
+
+/// Order outputs are rendered in. Only the display changes: the transaction, and so its
+/// txid, keeps its own order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputOrder {
+    /// Transaction order
+    #[default]
+    None,
+    /// Smallest value first
+    ValueAsc,
+    /// Largest value first
+    ValueDesc,
+}
+
+impl FromStr for OutputOrder {
+    type Err = String;
+
+    /// Parse `none`, `value-asc` or `value-desc`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(OutputOrder::None),
+            "value-asc" => Ok(OutputOrder::ValueAsc),
+            "value-desc" => Ok(OutputOrder::ValueDesc),
+            _ => Err("expected one of none, value-asc, value-desc".to_string()),
+        }
+    }
+}
+
+/// Indexes of the outputs in the order they are rendered. Outputs of equal value keep
+/// their transaction order.
+fn output_display_order(tx: &Transaction, order: OutputOrder) -> Vec<usize> {
+    let mut indexes: Vec<usize> = (0..tx.output.len()).collect();
+    match order {
+        OutputOrder::None => {}
+        OutputOrder::ValueAsc => indexes.sort_by_key(|&idx| tx.output[idx].value),
+        OutputOrder::ValueDesc => {
+            indexes.sort_by_key(|&idx| std::cmp::Reverse(tx.output[idx].value))
+        }
+    }
+    indexes
+}
+
+/// Sum of satoshi amounts, `None` if it doesn't fit in a u64. Values above 21M BTC are
+/// invalid but still decode, so their total can overflow.
+fn checked_total(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values
+        .into_iter()
+        .try_fold(0u64, |total, value| total.checked_add(value))
+}
+
+/// Total value of the transaction's outputs, see [`checked_total`]
+fn total_output_value(tx: &Transaction) -> Option<u64> {
+    checked_total(tx.output.iter().map(|output| output.value.to_sat()))
+}
+
+/// Outpoints consumed by the transaction's inputs, in input order
+pub fn spent_outpoints(tx: &Transaction) -> Vec<bitcoin::OutPoint> {
+    tx.input.iter().map(|input| input.previous_output).collect()
+}
+
+/// Outpoints created by the transaction's outputs, given the transaction's `txid`
+pub fn created_outpoints(txid: bitcoin::Txid, tx: &Transaction) -> Vec<bitcoin::OutPoint> {
+    (0..tx.output.len() as u32)
+        .map(|vout| bitcoin::OutPoint::new(txid, vout))
+        .collect()
+}
+
+/// A transaction of a batch spending an output of another transaction in the same batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackageEdge {
+    /// Batch index of the transaction whose output is spent
+    pub parent: usize,
+    pub output: u32,
+    /// Batch index of the spending transaction
+    pub child: usize,
+    pub input: usize,
+}
+
+/// Whether a transaction made it into a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    Unconfirmed,
+    /// Included in the block at `height`, whose header timestamp is `time` (Unix time)
+    Confirmed {
+        height: u64,
+        time: u64,
+    },
+}
+
+/// Where a transaction sits in a batch and the spends found between the batch's members
+#[derive(Clone, Debug, Default)]
+pub struct PackageContext {
+    pub index: usize,
+    pub edges: Vec<PackageEdge>,
+}
+
+/// Find the parent/child spends within a batch of `(index, transaction)` pairs
+pub fn package_edges<'a>(
+    txs: impl IntoIterator<Item = (usize, &'a Transaction)>,
+) -> Vec<PackageEdge> {
+    let txs: Vec<(usize, &Transaction)> = txs.into_iter().collect();
+    let by_txid: std::collections::HashMap<bitcoin::Txid, (usize, &Transaction)> = txs
+        .iter()
+        .map(|&(idx, tx)| (tx.compute_txid(), (idx, tx)))
+        .collect();
+
+    let mut edges = Vec::new();
+    for &(child, tx) in &txs {
+        for (input, txin) in tx.input.iter().enumerate() {
+            let outpoint = txin.previous_output;
+            if let Some(&(parent, parent_tx)) = by_txid.get(&outpoint.txid)
+                && (outpoint.vout as usize) < parent_tx.output.len()
+            {
+                edges.push(PackageEdge {
+                    parent,
+                    output: outpoint.vout,
+                    child,
+                    input,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// An outpoint spent by more than one transaction of a batch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub outpoint: bitcoin::OutPoint,
+    /// Batch indexes of the transactions spending it
+    pub spenders: Vec<usize>,
+}
+
+/// Find outpoints spent by several transactions of a batch of `(index, transaction)`
+/// pairs, e.g. an RBF replacement and the transaction it replaces
+pub fn batch_conflicts<'a>(
+    txs: impl IntoIterator<Item = (usize, &'a Transaction)>,
+) -> Vec<Conflict> {
+    let mut spends: Vec<Conflict> = Vec::new();
+    let mut by_outpoint = std::collections::HashMap::new();
+    for (idx, tx) in txs {
+        for outpoint in spent_outpoints(tx) {
+            if outpoint.is_null() {
+                continue;
+            }
+            let slot = *by_outpoint.entry(outpoint).or_insert_with(|| {
+                spends.push(Conflict {
+                    outpoint,
+                    spenders: Vec::new(),
+                });
+                spends.len() - 1
+            });
+            let spenders = &mut spends[slot].spenders;
+            // A transaction spending the same outpoint twice is invalid, not a conflict
+            if spenders.last() != Some(&idx) {
+                spenders.push(idx);
+            }
+        }
+    }
+    spends.retain(|conflict| conflict.spenders.len() > 1);
+    spends
+}
+
+/// Default size limit for decoded transactions, matching the 4 MWU block weight limit
+pub const DEFAULT_MAX_TX_SIZE: usize = 4_000_000;
+
+/// Decode a hex-encoded Bitcoin transaction
+pub fn decode_transaction(hex: &str) -> Result<Transaction, String> {
+    decode_transaction_limited(hex, DEFAULT_MAX_TX_SIZE)
+}
+
+/// Decode a hex-encoded Bitcoin transaction, rejecting it before allocating
+/// if it would exceed `max_bytes`
+pub fn decode_transaction_limited(hex: &str, max_bytes: usize) -> Result<Transaction, String> {
+    decode_transaction_bytes(&decode_hex_limited(hex, max_bytes)?)
+}
+
+/// Decode transaction hex to bytes without deserializing them, with the same checks
+/// as [`decode_transaction_limited`]
+pub fn decode_hex_limited(hex: &str, max_bytes: usize) -> Result<Vec<u8>, String> {
+    let hex = strip_hex(hex);
+    let size = hex.len() / 2;
+    if size > max_bytes {
+        return Err(format!(
+            "Transaction is {} bytes, exceeding the {} byte limit",
+            size, max_bytes
+        ));
+    }
+    if hex.len() % 2 == 1 {
+        return Err(format!(
+            "Invalid hex string: transaction hex has an odd number of characters ({}); did you truncate it or include a stray character?",
+            hex.len()
+        ));
+    }
+    hex::decode(hex).map_err(|e| format!("Invalid hex string: {}", e))
+}
+
+/// Trim whitespace and a leading `0x`, as left by copying hex from other tools
+pub fn strip_hex(hex: &str) -> &str {
+    let hex = hex.trim();
+    hex.strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex)
+}
+
+/// Decode a transaction whose bytes are the data part of a bech32 or bech32m string.
+/// The checksum limits such strings to 1023 characters, about 620 transaction bytes.
+pub fn decode_transaction_bech32(s: &str) -> Result<Transaction, String> {
+    decode_transaction_bytes(&decode_bech32_payload(s)?)
+}
+
+/// Data part of a bech32 or bech32m string, exactly as encoded
+pub fn decode_bech32_payload(s: &str) -> Result<Vec<u8>, String> {
+    use bitcoin::bech32::{self, hrp};
+
+    let (prefix, tx_bytes) =
+        bech32::decode(s.trim()).map_err(|e| format!("Invalid bech32 string: {}", e))?;
+    if [hrp::BC, hrp::TB, hrp::BCRT].contains(&prefix) {
+        return Err(format!(
+            "Invalid bech32 string: \"{}\" is the prefix of Bitcoin addresses, not transactions",
+            prefix
+        ));
+    }
+    Ok(tx_bytes)
+}
+
+/// Decode a raw serialized Bitcoin transaction
+pub fn decode_transaction_bytes(tx_bytes: &[u8]) -> Result<Transaction, String> {
+    info!("decoded {} bytes", tx_bytes.len());
+    encode::deserialize(tx_bytes).map_err(|e| {
+        if has_segwit_marker(tx_bytes) && matches!(e, encode::Error::ParseFailed(_)) {
+            format!(
+                "Failed to decode transaction: {} (SegWit-serialized without any witness data)",
+                e
+            )
+        } else {
+            format!("Failed to decode transaction: {}", e)
+        }
+    })
+}
+
+/// Legacy serialization of a transaction, without the SegWit marker, flag and witnesses.
+/// Its double SHA-256 is the txid.
+pub fn strip_witness(tx: &Transaction) -> Vec<u8> {
+    let mut stripped = tx.clone();
+    for input in &mut stripped.input {
+        input.witness.clear();
+    }
+    encode::serialize(&stripped)
+}
+
+/// Check that `tx_bytes`, from which `tx` was decoded, is exactly its canonical
+/// serialization, naming the field where they first differ
+pub fn verify_roundtrip(tx: &Transaction, tx_bytes: &[u8]) -> Result<(), String> {
+    let canonical = encode::serialize(tx);
+    let Some(offset) = canonical
+        .iter()
+        .zip(tx_bytes)
+        .position(|(a, b)| a != b)
+        .or((canonical.len() != tx_bytes.len()).then(|| canonical.len().min(tx_bytes.len())))
+    else {
+        return Ok(());
+    };
+    let field = serialization_fields(tx)
+        .into_iter()
+        .find(|field| offset < field.end)
+        .map_or_else(|| "trailing data".to_string(), |field| field.name);
+    Err(format!(
+        "Non-canonical encoding at byte {} ({})",
+        offset, field
+    ))
+}
+
+/// Kind of a field in the consensus serialization of a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+    Version,
+    Marker,
+    Count,
+    Outpoint,
+    ScriptSig,
+    Sequence,
+    Value,
+    ScriptPubKey,
+    Witness,
+    LockTime,
+}
+
+impl FieldKind {
+    const ALL: [FieldKind; 10] = [
+        FieldKind::Version,
+        FieldKind::Marker,
+        FieldKind::Count,
+        FieldKind::Outpoint,
+        FieldKind::ScriptSig,
+        FieldKind::Sequence,
+        FieldKind::Value,
+        FieldKind::ScriptPubKey,
+        FieldKind::Witness,
+        FieldKind::LockTime,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            FieldKind::Version => "version",
+            FieldKind::Marker => "SegWit marker and flag",
+            FieldKind::Count => "input/output count",
+            FieldKind::Outpoint => "previous output (txid, index)",
+            FieldKind::ScriptSig => "scriptSig",
+            FieldKind::Sequence => "sequence",
+            FieldKind::Value => "value",
+            FieldKind::ScriptPubKey => "scriptPubKey",
+            FieldKind::Witness => "witness",
+            FieldKind::LockTime => "lock time",
+        }
+    }
+}
+
+/// A field of the canonical serialization, ending at byte offset `end`
+struct SerializedField {
+    end: usize,
+    kind: FieldKind,
+    name: String,
+}
+
+/// Fields of the canonical serialization in order
+fn serialization_fields(tx: &Transaction) -> Vec<SerializedField> {
+    let mut fields = Vec::new();
+    let mut end = 0;
+    let mut push = |len: usize, kind: FieldKind, name: String| {
+        end += len;
+        fields.push(SerializedField { end, kind, name });
+    };
+    let segwit = tx.input.iter().any(|input| !input.witness.is_empty());
+
+    push(4, FieldKind::Version, "version".to_string());
+    if segwit {
+        push(2, FieldKind::Marker, "SegWit marker and flag".to_string());
+    }
+    push(
+        compact_size_len(tx.input.len()),
+        FieldKind::Count,
+        "input count".to_string(),
+    );
+    for (idx, input) in tx.input.iter().enumerate() {
+        push(
+            36,
+            FieldKind::Outpoint,
+            format!("input {} previous output", idx),
+        );
+        let script_len = input.script_sig.len();
+        push(
+            compact_size_len(script_len) + script_len,
+            FieldKind::ScriptSig,
+            format!("input {} scriptSig", idx),
+        );
+        push(4, FieldKind::Sequence, format!("input {} sequence", idx));
+    }
+    push(
+        compact_size_len(tx.output.len()),
+        FieldKind::Count,
+        "output count".to_string(),
+    );
+    for (idx, output) in tx.output.iter().enumerate() {
+        push(8, FieldKind::Value, format!("output {} value", idx));
+        let script_len = output.script_pubkey.len();
+        push(
+            compact_size_len(script_len) + script_len,
+            FieldKind::ScriptPubKey,
+            format!("output {} scriptPubKey", idx),
+        );
+    }
+    if segwit {
+        for (idx, input) in tx.input.iter().enumerate() {
+            push(
+                input.witness.size(),
+                FieldKind::Witness,
+                format!("input {} witness", idx),
+            );
+        }
+    }
+    push(4, FieldKind::LockTime, "lock time".to_string());
+    fields
+}
+
+/// Decode a PSBT given as base64 (as wallets export it) or hex
+pub fn decode_psbt(text: &str) -> Result<Psbt, String> {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    let text = text.trim();
+    let bytes = if text.starts_with("70736274ff") {
+        hex::decode(text).map_err(|e| format!("Invalid hex string: {}", e))?
+    } else {
+        STANDARD
+            .decode(text)
+            .map_err(|e| format!("Invalid base64 PSBT: {}", e))?
+    };
+    Psbt::deserialize(&bytes).map_err(|e| format!("Failed to decode PSBT: {}", e))
+}
+
+/// Amount in satoshis spent by each PSBT input, from its `witness_utxo` or else its
+/// `non_witness_utxo`. `None` when the input carries neither.
+pub fn psbt_input_amounts(psbt: &Psbt) -> Vec<Option<u64>> {
+    psbt.inputs
+        .iter()
+        .zip(&psbt.unsigned_tx.input)
+        .map(|(input, txin)| {
+            let utxo = input.witness_utxo.as_ref().or_else(|| {
+                input
+                    .non_witness_utxo
+                    .as_ref()?
+                    .output
+                    .get(txin.previous_output.vout as usize)
+            })?;
+            Some(utxo.value.to_sat())
+        })
+        .collect()
+}
+
+/// Count a noun, e.g. "1 input" or "3 outputs"
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Pair the inputs of `tx` with the outputs of `prev_tx` they spend. Inputs spending
+/// other transactions are `None`; it is an error if no input spends `prev_tx`.
+pub fn follow_spend(
+    tx: &Transaction,
+    prev_tx: &Transaction,
+) -> Result<Vec<Option<bitcoin::TxOut>>, String> {
+    let prev_txid = prev_tx.compute_txid();
+    let mut found = false;
+    let mut spent = Vec::with_capacity(tx.input.len());
+    for (idx, input) in tx.input.iter().enumerate() {
+        let outpoint = input.previous_output;
+        if outpoint.txid != prev_txid {
+            spent.push(None);
+            continue;
+        }
+        let output = prev_tx.output.get(outpoint.vout as usize).ok_or_else(|| {
+            format!(
+                "Input {} spends output {} but the previous transaction has {}",
+                idx,
+                outpoint.vout,
+                plural(prev_tx.output.len(), "output")
+            )
+        })?;
+        found = true;
+        spent.push(Some(output.clone()));
+    }
+    if !found {
+        return Err(format!(
+            "Previous transaction {} is not spent by any input",
+            prev_txid
+        ));
+    }
+    Ok(spent)
+}
+
+/// Transactions decoded from a blob prefixed with a compact-size transaction count
+#[derive(Debug)]
+pub struct TransactionList {
+    /// Count stated by the prefix
+    pub declared_count: u64,
+    pub transactions: Vec<Transaction>,
+    /// Why decoding stopped before the end of the blob, if it did
+    pub error: Option<String>,
+}
+
+impl TransactionList {
+    /// Describe a mismatch between the prefix and what was decoded
+    pub fn warning(&self) -> Option<String> {
+        let decoded = self.transactions.len() as u64;
+        match &self.error {
+            Some(e) => Some(format!(
+                "prefix declares {} transactions but decoding stopped after {}: {}",
+                self.declared_count, decoded, e
+            )),
+            None if decoded != self.declared_count => Some(format!(
+                "prefix declares {} transactions but the blob holds {}",
+                self.declared_count, decoded
+            )),
+            None => None,
+        }
+    }
+}
+
+/// Decode a compact-size count followed by back-to-back transactions (a block body
+/// without the header). Decodes until the bytes run out, whatever the declared count.
+pub fn decode_transaction_list(tx_bytes: &[u8]) -> Result<TransactionList, String> {
+    use bitcoin::consensus::Decodable;
+
+    let mut reader = tx_bytes;
+    let declared_count = encode::VarInt::consensus_decode(&mut reader)
+        .map_err(|e| format!("Failed to read transaction count: {}", e))?
+        .0;
+
+    let mut transactions = Vec::new();
+    let mut error = None;
+    while !reader.is_empty() {
+        match Transaction::consensus_decode(&mut reader) {
+            Ok(tx) => transactions.push(tx),
+            Err(e) => {
+                error = Some(format!("Failed to decode transaction: {}", e));
+                break;
+            }
+        }
+    }
+
+    Ok(TransactionList {
+        declared_count,
+        transactions,
+        error,
+    })
+}
+
+/// Check for the SegWit marker and flag (0x00 0x01) right after the version
+fn has_segwit_marker(tx_bytes: &[u8]) -> bool {
+    tx_bytes.get(4..6) == Some(&[0x00, 0x01])
+}
+
+/// Whether any input carries witness data
+pub fn has_witness(tx: &Transaction) -> bool {
+    tx.input.iter().any(|input| !input.witness.is_empty())
+}
+
+/// Whether every input other than a coinbase carries witness data. A coinbase counts
+/// only through its witness reserved value, so it is fully SegWit when it has one.
+pub fn is_fully_segwit(tx: &Transaction) -> bool {
+    has_witness(tx)
+        && tx
+            .input
+            .iter()
+            .filter(|input| !input.previous_output.is_null())
+            .all(|input| !input.witness.is_empty())
+}
+
+/// Name the serialization format of a decoded transaction. The decoder rejects the
+/// SegWit marker when every witness is empty, so the raw bytes used the SegWit format
+/// exactly when some input carries witness data.
+fn serialization_format(tx: &Transaction) -> &'static str {
+    if has_witness(tx) {
+        "SegWit (marker 0x00 0x01)"
+    } else {
+        "Legacy"
+    }
+}
+
+/// Compute the fee from the values of the spent outputs, one per input
+pub fn compute_fee(tx: &Transaction, prevout_values: &[u64]) -> Result<u64, String> {
+    if prevout_values.len() != tx.input.len() {
+        return Err(format!(
+            "Expected {} prevout values (one per input), got {}",
+            tx.input.len(),
+            prevout_values.len()
+        ));
+    }
+
+    let total_input = checked_total(prevout_values.iter().copied())
+        .ok_or("Total prevout value overflows a 64-bit satoshi count")?;
+    let total_output =
+        total_output_value(tx).ok_or("Total output value overflows a 64-bit satoshi count")?;
+    total_input.checked_sub(total_output).ok_or_else(|| {
+        format!(
+            "Prevout values ({} sats) are less than the total output value ({} sats)",
+            total_input, total_output
+        )
+    })
+}
+
+/// Label a fee rate (sat/vB) with its bucket and a mempool-priority hint
+fn fee_rate_bucket(fee_rate: f64, buckets: &FeeBuckets) -> String {
+    if fee_rate < buckets.low {
+        format!(
+            "low (<{} sat/vB), may wait for a quiet mempool",
+            buckets.low
+        )
+    } else if fee_rate < buckets.medium {
+        format!(
+            "medium ({}-{} sat/vB), likely to confirm within hours",
+            buckets.low, buckets.medium
+        )
+    } else if fee_rate < buckets.high {
+        format!(
+            "high ({}-{} sat/vB), likely to confirm within a few blocks",
+            buckets.medium, buckets.high
+        )
+    } else {
+        format!("very high (>={} sat/vB), likely next block", buckets.high)
+    }
+}
+
+/// Calculate the byte length of a Bitcoin compact size (varint) encoding
+fn compact_size_len(n: usize) -> usize {
+    if n <= 0xfc {
+        1
+    } else if n <= 0xffff {
+        3
+    } else if n <= 0xffff_ffff {
+        5
+    } else {
+        9
+    }
+}
+
+/// Calculate the virtual size of a single input (including its witness data)
+fn input_vsize(input: &bitcoin::TxIn) -> usize {
+    // Non-witness (base) data:
+    // previous_output: txid (32) + vout (4) = 36
+    // script_sig: compact_size(len) + script bytes
+    // sequence: 4
+    let script_sig_len = input.script_sig.len();
+    let base_size = 36 + compact_size_len(script_sig_len) + script_sig_len + 4;
+
+    // Witness data (scaled at 1/4 weight)
+    let weight = base_size * 4 + witness_size(input);
+    weight.div_ceil(4)
+}
+
+/// Calculate the serialized size of an input's witness: the item count
+/// followed by each item's compact size length and bytes
+fn witness_size(input: &bitcoin::TxIn) -> usize {
+    if input.witness.is_empty() {
+        return 0;
+    }
+
+    let mut size = compact_size_len(input.witness.len()); // number of witness items
+    for item in input.witness.iter() {
+        size += compact_size_len(item.len()) + item.len();
+    }
+    size
+}
+
+/// Consensus-serialized size of an input in bytes, as `(base, witness)`. The witness is
+/// serialized apart from the rest of the input, after all the outputs.
+fn input_serialized_size(input: &bitcoin::TxIn) -> (usize, usize) {
+    (encode::serialize(input).len(), witness_size(input))
+}
+
+/// Calculate the virtual size of a single output
+fn output_vsize(output: &bitcoin::TxOut) -> usize {
+    // Outputs are entirely non-witness data:
+    // value: 8 bytes
+    // script_pubkey: compact_size(len) + script bytes
+    let script_len = output.script_pubkey.len();
+    8 + compact_size_len(script_len) + script_len
+}
+
+/// Scripts larger than this can never be executed (Core's MAX_SCRIPT_SIZE)
+const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// Mainnet addresses with no known private key, commonly used to burn coins
+const KNOWN_BURN_ADDRESSES: &[&str] = &[
+    "1BitcoinEaterAddressDontSendf59kuE",
+    "1111111111111111111114oLvT2",
+];
+
+/// Explain why an output's value is permanently unspendable, if it is.
+/// Follows Core's `IsUnspendable` (OP_RETURN or oversized script) plus known burn addresses.
+/// Zero-value outputs are not burns, so data-carrier OP_RETURNs are not flagged.
+fn burn_reason(output: &bitcoin::TxOut) -> Option<&'static str> {
+    if output.value.to_sat() == 0 {
+        return None;
+    }
+
+    let script = &output.script_pubkey;
+    if script.is_op_return() {
+        Some("OP_RETURN with value")
+    } else if script.len() > MAX_SCRIPT_SIZE {
+        Some("script exceeds 10,000 bytes")
+    } else if KNOWN_BURN_ADDRESSES.iter().any(|addr| {
+        addr.parse::<bitcoin::Address<_>>()
+            .is_ok_and(|a| a.assume_checked().script_pubkey() == *script)
+    }) {
+        Some("known burn address")
+    } else {
+        None
+    }
+}
+
+/// Witness version and program of a witness-program scriptPubKey: `OP_0`..`OP_16`
+/// followed by a single 2 to 40 byte push (exactly 20 or 32 bytes for version 0)
+fn witness_program(script: &bitcoin::Script) -> Option<(u8, Vec<u8>)> {
+    let version = script.witness_version()?;
+    let program = bitcoin::WitnessProgram::new(version, &script.as_bytes()[2..]).ok()?;
+    Some((version.to_num(), program.program().as_bytes().to_vec()))
+}
+
+/// How an output can be spent under today's consensus rules
+fn spendability(output: &bitcoin::TxOut) -> &'static str {
+    match core_script_type(output) {
+        "pubkey" | "pubkeyhash" | "witness_v0_keyhash" => "Spendable via signature",
+        "multisig" => "Spendable via signatures from the listed keys",
+        "scripthash" | "witness_v0_scripthash" => {
+            "Spendable by revealing and satisfying the committed script"
+        }
+        "witness_v1_taproot" => {
+            "Spendable via signature (key path) or a committed script (script path)"
+        }
+        "anchor" => "Anyone-can-spend, no signature needed (P2A)",
+        "nulldata" => "Provably unspendable (OP_RETURN)",
+        "witness_unknown" => match witness_program(&output.script_pubkey) {
+            Some(_) => "Anyone-can-spend under current rules (undefined witness program)",
+            None => "Unspendable: version 0 witness programs must be 20 or 32 bytes",
+        },
+        _ if is_anyone_can_spend(output) => "Anyone-can-spend, no signature needed",
+        _ => "Non-standard script, spendable only if its conditions can be met",
+    }
+}
+
+/// Check if an output is a Pay-to-Anchor (P2A) / Ephemeral Anchor output
+/// P2A is OP_1 <0x4e73> (witness v1 with 2-byte program 0x4e73)
+fn is_ephemeral_anchor(output: &bitcoin::TxOut) -> bool {
+    witness_program(&output.script_pubkey) == Some((1, vec![0x4e, 0x73]))
+}
+
+/// Witness version of an output committing to a not yet defined SegWit version:
+/// `OP_2`..`OP_16` followed by a single 2 to 40 byte push
+fn future_witness_version(output: &bitcoin::TxOut) -> Option<u8> {
+    let (version, _) = witness_program(&output.script_pubkey)?;
+    (version >= 2).then_some(version)
+}
+
+/// Tweaked x-only output key committed to by a P2TR output
+fn taproot_output_key(output: &bitcoin::TxOut) -> Option<bitcoin::XOnlyPublicKey> {
+    if !output.script_pubkey.is_p2tr() {
+        return None;
+    }
+    bitcoin::XOnlyPublicKey::from_slice(&output.script_pubkey.as_bytes()[2..]).ok()
+}
+
+/// Check that `output_key` is `internal_key` tweaked with `merkle_root` (BIP341)
+fn taproot_tweak_matches(
+    output_key: bitcoin::XOnlyPublicKey,
+    internal_key: bitcoin::key::UntweakedPublicKey,
+    merkle_root: Option<bitcoin::TapNodeHash>,
+) -> bool {
+    use bitcoin::key::TapTweak;
+
+    let secp = bitcoin::secp256k1::Secp256k1::verification_only();
+    let (tweaked, _) = internal_key.tap_tweak(&secp, merkle_root);
+    tweaked.to_x_only_public_key() == output_key
+}
+
+/// Check if an output can be spent by anyone without a signature: an empty scriptPubKey
+/// (`[]`, satisfied by any scriptSig pushing true) or a lone constant `[0x51..=0x60]`
+/// (OP_1/OP_TRUE through OP_16). P2A anchors are excluded, see [`is_ephemeral_anchor`].
+fn is_anyone_can_spend(output: &bitcoin::TxOut) -> bool {
+    matches!(output.script_pubkey.as_bytes(), [] | [0x51..=0x60])
+}
+
+/// Lightning commitment transaction components that can be recognised heuristically
+#[derive(Debug, PartialEq)]
+enum LightningHint {
+    ToLocal,
+    OfferedHtlc,
+    ReceivedHtlc,
+    Anchor,
+}
+
+impl LightningHint {
+    fn label(&self) -> &'static str {
+        match self {
+            LightningHint::ToLocal => "to_local",
+            LightningHint::OfferedHtlc => "offered HTLC",
+            LightningHint::ReceivedHtlc => "received HTLC",
+            LightningHint::Anchor => "anchor",
+        }
+    }
+}
+
+/// Value of a BOLT 3 anchor output in satoshis
+const LN_ANCHOR_VALUE: u64 = 330;
+
+/// Simplified view of a script instruction used for template matching
+#[derive(Clone, Copy, PartialEq)]
+enum ScriptToken {
+    Push(usize),
+    Op(bitcoin::opcodes::Opcode),
+}
+
+fn script_tokens(script: &bitcoin::Script) -> Option<Vec<ScriptToken>> {
+    script
+        .instructions()
+        .map(|ins| match ins {
+            Ok(bitcoin::script::Instruction::PushBytes(bytes)) => {
+                Some(ScriptToken::Push(bytes.len()))
+            }
+            Ok(bitcoin::script::Instruction::Op(op)) => Some(ScriptToken::Op(op)),
+            Err(_) => None,
+        })
+        .collect()
+}
+
+/// Recognise BOLT 3 commitment scripts revealed as a P2WSH witness script.
+/// These are heuristics: the same shapes can appear in unrelated contracts.
+fn lightning_script_hint(script: &bitcoin::Script) -> Option<LightningHint> {
+    use ScriptToken::{Op, Push};
+    use bitcoin::opcodes::all::*;
+
+    let tokens = script_tokens(script)?;
+
+    // to_local: OP_IF <revocationpubkey> OP_ELSE <to_self_delay> OP_CSV OP_DROP
+    //           <local_delayedpubkey> OP_ENDIF OP_CHECKSIG
+    if let [
+        Op(OP_IF),
+        Push(33),
+        Op(OP_ELSE),
+        delay,
+        Op(OP_CSV),
+        Op(OP_DROP),
+        Push(33),
+        Op(OP_ENDIF),
+        Op(OP_CHECKSIG),
+    ] = tokens.as_slice()
+    {
+        let is_number = match delay {
+            Push(len) => *len <= 4,
+            Op(op) => (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&op.to_u8()),
+        };
+        if is_number {
+            return Some(LightningHint::ToLocal);
+        }
+    }
+
+    // anchor: <funding_pubkey> OP_CHECKSIG OP_IFDUP OP_NOTIF OP_16 OP_CSV OP_ENDIF
+    if tokens
+        == [
+            Push(33),
+            Op(OP_CHECKSIG),
+            Op(OP_IFDUP),
+            Op(OP_NOTIF),
+            Op(OP_PUSHNUM_16),
+            Op(OP_CSV),
+            Op(OP_ENDIF),
+        ]
+    {
+        return Some(LightningHint::Anchor);
+    }
+
+    // HTLCs share a revocation branch, then diverge on the preimage size check:
+    // OP_DUP OP_HASH160 <revocation hash> OP_EQUAL OP_IF OP_CHECKSIG
+    // OP_ELSE <remote_htlcpubkey> OP_SWAP OP_SIZE 32 OP_EQUAL (OP_NOTIF | OP_IF) ...
+    let htlc_prefix = [
+        Op(OP_DUP),
+        Op(OP_HASH160),
+        Push(20),
+        Op(OP_EQUAL),
+        Op(OP_IF),
+        Op(OP_CHECKSIG),
+        Op(OP_ELSE),
+        Push(33),
+        Op(OP_SWAP),
+        Op(OP_SIZE),
+        Push(1),
+        Op(OP_EQUAL),
+    ];
+    if tokens.starts_with(&htlc_prefix) {
+        match tokens.get(htlc_prefix.len()) {
+            Some(Op(OP_NOTIF)) => return Some(LightningHint::OfferedHtlc),
+            Some(Op(OP_IF)) => return Some(LightningHint::ReceivedHtlc),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Guess whether an input spends a Lightning commitment output from its witness script
+fn lightning_input_hint(input: &bitcoin::TxIn) -> Option<LightningHint> {
+    if input.witness.len() < 2 {
+        return None;
+    }
+    let witness_script = bitcoin::Script::from_bytes(input.witness.last()?);
+    let hint = lightning_script_hint(witness_script)?;
+    debug!(
+        "witness script matches the Lightning {} template",
+        hint.label()
+    );
+    Some(hint)
+}
+
+/// Guess whether an output is a Lightning anchor (330-sat P2WSH)
+fn lightning_output_hint(output: &bitcoin::TxOut) -> Option<LightningHint> {
+    if output.value.to_sat() == LN_ANCHOR_VALUE && output.script_pubkey.is_p2wsh() {
+        Some(LightningHint::Anchor)
+    } else {
+        None
+    }
+}
+
+/// Ordinals inscription carried in a Taproot script-path spend
+#[derive(Debug, PartialEq)]
+struct Inscription {
+    content_type: Option<String>,
+    content: Vec<u8>,
+}
+
+/// Longest text preview shown for an inscription
+const INSCRIPTION_PREVIEW_CHARS: usize = 80;
+
+/// Extract the first `OP_FALSE OP_IF "ord" ... OP_ENDIF` envelope from a tapscript
+fn parse_inscription(witness_script: &bitcoin::Script) -> Option<Inscription> {
+    use bitcoin::opcodes::all::{OP_ENDIF, OP_IF};
+    use bitcoin::script::Instruction;
+
+    // Inscription field tags
+    const TAG_CONTENT_TYPE: &[u8] = &[1];
+
+    let instructions: Vec<Instruction> = witness_script
+        .instructions()
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let start = instructions.windows(3).position(|window| {
+        matches!(
+            window,
+            [Instruction::PushBytes(empty), Instruction::Op(OP_IF), Instruction::PushBytes(protocol)]
+                if empty.is_empty() && protocol.as_bytes() == b"ord"
+        )
+    })?;
+
+    let mut content_type = None;
+    let mut content = Vec::new();
+    let mut fields = instructions[start + 3..].iter();
+    loop {
+        let tag = match fields.next()? {
+            Instruction::Op(OP_ENDIF) => break,
+            // An empty push separates the fields from the body
+            Instruction::PushBytes(push) if push.is_empty() => {
+                for chunk in fields.by_ref() {
+                    match chunk {
+                        Instruction::PushBytes(push) => content.extend_from_slice(push.as_bytes()),
+                        Instruction::Op(OP_ENDIF) => break,
+                        Instruction::Op(_) => return None,
+                    }
+                }
+                break;
+            }
+            Instruction::PushBytes(push) => push.as_bytes().to_vec(),
+            // OP_PUSHNUM_1 ..= OP_PUSHNUM_16 encode small tags
+            Instruction::Op(op) => vec![op.to_u8().checked_sub(0x50)?],
+        };
+        let Instruction::PushBytes(value) = fields.next()? else {
+            return None;
+        };
+        if tag == TAG_CONTENT_TYPE {
+            content_type = Some(String::from_utf8_lossy(value.as_bytes()).into_owned());
+        }
+    }
+
+    Some(Inscription {
+        content_type,
+        content,
+    })
+}
+
+/// Rune identifier: the block height and index of the transaction that etched it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct RuneId {
+    block: u64,
+    tx: u32,
+}
+
+impl RuneId {
+    fn new(block: u128, tx: u128) -> Option<RuneId> {
+        let block = u64::try_from(block).ok()?;
+        let tx = u32::try_from(tx).ok()?;
+        // Block 0 only holds the reserved rune 0:0
+        if block == 0 && tx > 0 {
+            return None;
+        }
+        Some(RuneId { block, tx })
+    }
+
+    /// Edict IDs are delta-encoded; the tx index is absolute once the block changes
+    fn next(self, block_delta: u128, tx_delta: u128) -> Option<RuneId> {
+        let block = u128::from(self.block).checked_add(block_delta)?;
+        let tx = if block_delta == 0 {
+            u128::from(self.tx).checked_add(tx_delta)?
+        } else {
+            tx_delta
+        };
+        RuneId::new(block, tx)
+    }
+}
+
+impl fmt::Display for RuneId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.block, self.tx)
+    }
+}
+
+/// Transfer of `amount` units of a rune to output `output`
+#[derive(Debug, PartialEq)]
+struct Edict {
+    id: RuneId,
+    amount: u128,
+    output: u128,
+}
+
+/// Open mint terms of an etched rune
+#[derive(Debug, Default, PartialEq)]
+struct RuneTerms {
+    cap: Option<u128>,
+    amount: Option<u128>,
+    height: (Option<u64>, Option<u64>),
+    offset: (Option<u64>, Option<u64>),
+}
+
+/// Creation of a new rune
+#[derive(Debug, Default, PartialEq)]
+struct Etching {
+    /// Name with its spacers, or `None` for a reserved name assigned on etching
+    rune: Option<String>,
+    divisibility: Option<u8>,
+    symbol: Option<char>,
+    premine: Option<u128>,
+    terms: Option<RuneTerms>,
+    turbo: bool,
+}
+
+/// Runes protocol message carried in an `OP_RETURN OP_13` output
+#[derive(Debug, Default, PartialEq)]
+struct Runestone {
+    edicts: Vec<Edict>,
+    etching: Option<Etching>,
+    mint: Option<RuneId>,
+    pointer: Option<u32>,
+    /// Why the runestone is malformed. Runes sent to a cenotaph are burned.
+    cenotaph: Option<&'static str>,
+}
+
+/// Decode an unsigned LEB128 integer, returning it with its length in bytes
+fn read_leb128(bytes: &[u8]) -> Option<(u128, usize)> {
+    let mut n = 0u128;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let value = u128::from(byte & 0x7f);
+        // The 19th byte may only carry the top 2 bits of a u128
+        if i > 18 || (i == 18 && value & 0x7c != 0) {
+            return None;
+        }
+        n |= value << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((n, i + 1));
+        }
+    }
+    None
+}
+
+/// Rune name of a number in bijective base 26: 0 is "A", 25 "Z", 26 "AA".
+/// Bit `i` of `spacers` puts a "•" after the `i`th letter.
+fn rune_name(n: u128, spacers: u32) -> String {
+    let mut letters = Vec::new();
+    if n == u128::MAX {
+        letters.extend(b"BCGDENLQRQWDSLRUGSNLBTMFIJAV");
+    } else {
+        let mut n = n + 1;
+        while n > 0 {
+            letters.push(b'A' + ((n - 1) % 26) as u8);
+            n = (n - 1) / 26;
+        }
+        letters.reverse();
+    }
+    let mut name = String::new();
+    for (i, &letter) in letters.iter().enumerate() {
+        name.push(letter as char);
+        if i + 1 < letters.len() && spacers & (1 << i) != 0 {
+            name.push('•');
+        }
+    }
+    name
+}
+
+/// Pop the first value of a runestone field if `valid` accepts it. Rejected values
+/// stay behind, which makes the runestone a cenotaph when the tag is even.
+fn take_rune_field(
+    fields: &mut std::collections::BTreeMap<u128, std::collections::VecDeque<u128>>,
+    tag: u128,
+    valid: impl Fn(u128) -> bool,
+) -> Option<u128> {
+    let values = fields.get_mut(&tag)?;
+    let value = *values.front()?;
+    if !valid(value) {
+        return None;
+    }
+    values.pop_front();
+    Some(value)
+}
+
+/// Decode the runestone in an `OP_RETURN OP_13` script: tag/value pairs of LEB128
+/// integers, followed by the edicts after a zero tag. Malformed messages are returned
+/// as cenotaphs rather than rejected, since they still burn the runes sent to them.
+fn decode_runestone(script: &bitcoin::Script) -> Option<Runestone> {
+    use bitcoin::opcodes::all::{OP_PUSHNUM_13, OP_RETURN};
+    use bitcoin::script::Instruction;
+
+    // Field tags; unknown even tags make a cenotaph, unknown odd tags are ignored
+    const TAG_BODY: u128 = 0;
+    const TAG_DIVISIBILITY: u128 = 1;
+    const TAG_FLAGS: u128 = 2;
+    const TAG_SPACERS: u128 = 3;
+    const TAG_RUNE: u128 = 4;
+    const TAG_SYMBOL: u128 = 5;
+    const TAG_PREMINE: u128 = 6;
+    const TAG_CAP: u128 = 8;
+    const TAG_AMOUNT: u128 = 10;
+    const TAG_HEIGHT_START: u128 = 12;
+    const TAG_HEIGHT_END: u128 = 14;
+    const TAG_OFFSET_START: u128 = 16;
+    const TAG_OFFSET_END: u128 = 18;
+    const TAG_MINT: u128 = 20;
+    const TAG_POINTER: u128 = 22;
+
+    const FLAG_ETCHING: u128 = 1;
+    const FLAG_TERMS: u128 = 1 << 1;
+    const FLAG_TURBO: u128 = 1 << 2;
+
+    const MAX_DIVISIBILITY: u128 = 38;
+    const MAX_SPACERS: u128 = (1 << 27) - 1;
+
+    let mut instructions = script.instructions();
+    if !matches!(instructions.next(), Some(Ok(Instruction::Op(OP_RETURN))))
+        || !matches!(
+            instructions.next(),
+            Some(Ok(Instruction::Op(OP_PUSHNUM_13)))
+        )
+    {
+        return None;
+    }
+    let cenotaph = |reason| {
+        Some(Runestone {
+            cenotaph: Some(reason),
+            ..Runestone::default()
+        })
+    };
+
+    let mut payload = Vec::new();
+    for instruction in instructions {
+        match instruction {
+            Ok(Instruction::PushBytes(push)) => payload.extend_from_slice(push.as_bytes()),
+            Ok(Instruction::Op(_)) => return cenotaph("opcode in the payload"),
+            Err(_) => return cenotaph("invalid script"),
+        }
+    }
+    let mut integers = Vec::new();
+    let mut rest = payload.as_slice();
+    while !rest.is_empty() {
+        let Some((n, len)) = read_leb128(rest) else {
+            return cenotaph("malformed LEB128 integer");
+        };
+        integers.push(n);
+        rest = &rest[len..];
+    }
+
+    let mut runestone = Runestone::default();
+    let mut fields = std::collections::BTreeMap::<u128, std::collections::VecDeque<u128>>::new();
+    let mut i = 0;
+    while i < integers.len() {
+        let tag = integers[i];
+        if tag == TAG_BODY {
+            let mut id = RuneId::default();
+            for edict in integers[i + 1..].chunks(4) {
+                let &[block, tx, amount, output] = edict else {
+                    runestone
+                        .cenotaph
+                        .get_or_insert("trailing integers after the edicts");
+                    break;
+                };
+                let Some(next) = id.next(block, tx) else {
+                    runestone
+                        .cenotaph
+                        .get_or_insert("invalid rune ID in an edict");
+                    break;
+                };
+                id = next;
+                runestone.edicts.push(Edict { id, amount, output });
+            }
+            break;
+        }
+        let Some(&value) = integers.get(i + 1) else {
+            runestone.cenotaph.get_or_insert("truncated field");
+            break;
+        };
+        fields.entry(tag).or_default().push_back(value);
+        i += 2;
+    }
+
+    let mut flags = take_rune_field(&mut fields, TAG_FLAGS, |_| true).unwrap_or(0);
+    let mut take_flag = |flag: u128| {
+        let set = flags & flag != 0;
+        flags &= !flag;
+        set
+    };
+    let etching = take_flag(FLAG_ETCHING);
+    let terms = take_flag(FLAG_TERMS);
+    let turbo = take_flag(FLAG_TURBO);
+
+    let fits_u64 = |value| u64::try_from(value).is_ok();
+    if etching {
+        let rune = take_rune_field(&mut fields, TAG_RUNE, |_| true);
+        let spacers = take_rune_field(&mut fields, TAG_SPACERS, |v| v <= MAX_SPACERS);
+        let symbol = take_rune_field(&mut fields, TAG_SYMBOL, |v| {
+            u32::try_from(v).ok().and_then(char::from_u32).is_some()
+        });
+        let terms = terms.then(|| RuneTerms {
+            cap: take_rune_field(&mut fields, TAG_CAP, |_| true),
+            amount: take_rune_field(&mut fields, TAG_AMOUNT, |_| true),
+            height: (
+                take_rune_field(&mut fields, TAG_HEIGHT_START, fits_u64).map(|v| v as u64),
+                take_rune_field(&mut fields, TAG_HEIGHT_END, fits_u64).map(|v| v as u64),
+            ),
+            offset: (
+                take_rune_field(&mut fields, TAG_OFFSET_START, fits_u64).map(|v| v as u64),
+                take_rune_field(&mut fields, TAG_OFFSET_END, fits_u64).map(|v| v as u64),
+            ),
+        });
+        let etching = Etching {
+            rune: rune.map(|n| rune_name(n, spacers.unwrap_or(0) as u32)),
+            divisibility: take_rune_field(&mut fields, TAG_DIVISIBILITY, |v| v <= MAX_DIVISIBILITY)
+                .map(|v| v as u8),
+            symbol: symbol.and_then(|v| char::from_u32(v as u32)),
+            premine: take_rune_field(&mut fields, TAG_PREMINE, |_| true),
+            terms,
+            turbo,
+        };
+        let minted = etching.terms.as_ref().map_or(Some(0), |terms| {
+            terms
+                .cap
+                .unwrap_or(0)
+                .checked_mul(terms.amount.unwrap_or(0))
+        });
+        if minted
+            .and_then(|minted| minted.checked_add(etching.premine.unwrap_or(0)))
+            .is_none()
+        {
+            runestone.cenotaph.get_or_insert("supply overflows");
+        }
+        runestone.etching = Some(etching);
+    }
+
+    // A mint names its rune with two consecutive values
+    runestone.mint = fields.get_mut(&TAG_MINT).and_then(|values| {
+        let id = RuneId::new(*values.front()?, *values.get(1)?)?;
+        values.drain(..2);
+        Some(id)
+    });
+    runestone.pointer =
+        take_rune_field(&mut fields, TAG_POINTER, |v| u32::try_from(v).is_ok()).map(|v| v as u32);
+
+    if flags != 0 {
+        runestone.cenotaph.get_or_insert("unrecognized flag");
+    }
+    if fields
+        .iter()
+        .any(|(tag, values)| tag % 2 == 0 && !values.is_empty())
+    {
+        runestone.cenotaph.get_or_insert("unrecognized even tag");
+    }
+    Some(runestone)
+}
+
+/// The runestone of a transaction, from its first `OP_RETURN OP_13` output, with the
+/// index of that output
+fn tx_runestone(tx: &Transaction) -> Option<(usize, Runestone)> {
+    let (idx, mut runestone) = tx
+        .output
+        .iter()
+        .enumerate()
+        .find_map(|(idx, output)| Some((idx, decode_runestone(&output.script_pubkey)?)))?;
+    // Output `n` itself splits the amount between all non-OP_RETURN outputs
+    if runestone
+        .edicts
+        .iter()
+        .any(|edict| edict.output > tx.output.len() as u128)
+    {
+        runestone
+            .cenotaph
+            .get_or_insert("edict to a missing output");
+    }
+    Some((idx, runestone))
+}
+
+/// Parse the control block revealed by a Taproot script-path spend
+fn taproot_control_block(input: &bitcoin::TxIn) -> Option<bitcoin::taproot::ControlBlock> {
+    if detect_input_type(input) != InputType::P2trScriptPath {
+        return None;
+    }
+    bitcoin::taproot::ControlBlock::decode(input.witness.taproot_control_block()?).ok()
+}
+
+/// Find an inscription in an input's tapscript, if it has one
+fn input_inscription(input: &bitcoin::TxIn) -> Option<Inscription> {
+    let leaf = input.witness.taproot_leaf_script()?;
+    if leaf.version != bitcoin::taproot::LeafVersion::TapScript {
+        return None;
+    }
+    parse_inscription(leaf.script)
+}
+
+/// What a witness stack item looks like, judged from its length and leading bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessItemKind {
+    Empty,
+    /// DER-encoded ECDSA signature with its sighash byte, and the reason it isn't
+    /// strict DER (BIP66 and low S) if it isn't
+    EcdsaSignature {
+        non_strict: Option<&'static str>,
+    },
+    /// 64 bytes, plus a sighash byte unless it's SIGHASH_DEFAULT
+    SchnorrSignature,
+    CompressedPubkey,
+    UncompressedPubkey,
+    /// Taproot control block: leaf version byte, internal key and 32-byte path hashes.
+    /// A 65-byte control block can't be told apart from a Schnorr signature and is
+    /// reported as one.
+    ControlBlock,
+    /// Over 100 bytes, likely a witness script
+    Script,
+    Data(usize),
+}
+
+/// Classify a witness item by its shape. Nothing is verified beyond the encoding.
+pub fn classify_witness_item(item: &[u8]) -> WitnessItemKind {
+    let len = item.len();
+    match len {
+        0 => WitnessItemKind::Empty,
+        // About one Schnorr signature in 256 starts with 0x30, so at their lengths only a
+        // sequence of two integers is taken for a DER signature
+        64 | 65 if is_der_sequence(item) => WitnessItemKind::EcdsaSignature {
+            non_strict: check_strict_der(item).err(),
+        },
+        9..=63 | 66..=73 if item[0] == 0x30 => WitnessItemKind::EcdsaSignature {
+            non_strict: check_strict_der(item).err(),
+        },
+        33 if matches!(item[0], 0x02 | 0x03) => WitnessItemKind::CompressedPubkey,
+        65 if item[0] == 0x04 => WitnessItemKind::UncompressedPubkey,
+        64 | 65 => WitnessItemKind::SchnorrSignature,
+        _ if len >= 33 && (len - 33).is_multiple_of(32) && item[0] & 0xfe == 0xc0 => {
+            WitnessItemKind::ControlBlock
+        }
+        101.. => WitnessItemKind::Script,
+        _ => WitnessItemKind::Data(len),
+    }
+}
+
+/// Whether `sig` is laid out like a DER signature with its sighash byte: a sequence of
+/// two integers whose lengths add up, whatever their values
+fn is_der_sequence(sig: &[u8]) -> bool {
+    let len = sig.len();
+    if !(9..=73).contains(&len) || sig[0] != 0x30 || sig[1] as usize != len - 3 || sig[2] != 0x02 {
+        return false;
+    }
+    let len_r = sig[3] as usize;
+    5 + len_r < len && sig[4 + len_r] == 0x02 && len_r + sig[5 + len_r] as usize + 7 == len
+}
+
+/// Half the secp256k1 group order; signatures with a larger S are malleable (BIP62 low-S)
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Check the encoding (not the validity) of a DER signature followed by its sighash byte,
+/// following Bitcoin Core's `IsValidSignatureEncoding` (BIP66) and `IsLowDERSignature`:
+/// `0x30 <len> 0x02 <len R> <R> 0x02 <len S> <S> <sighash>`
+fn check_strict_der(sig: &[u8]) -> Result<(), &'static str> {
+    if !(9..=73).contains(&sig.len()) {
+        return Err("bad length");
+    }
+    if sig[0] != 0x30 {
+        return Err("missing 0x30 sequence tag");
+    }
+    if sig[1] as usize != sig.len() - 3 {
+        return Err("sequence length doesn't match");
+    }
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return Err("R length out of bounds");
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != sig.len() {
+        return Err("R and S lengths don't add up");
+    }
+
+    // R and S: tag position and length
+    for (tag_pos, len) in [(2, len_r), (len_r + 4, len_s)] {
+        if sig[tag_pos] != 0x02 {
+            return Err("missing 0x02 integer tag");
+        }
+        if len == 0 {
+            return Err("zero-length integer");
+        }
+        let value = &sig[tag_pos + 2..tag_pos + 2 + len];
+        if value[0] & 0x80 != 0 {
+            return Err("negative integer");
+        }
+        if len > 1 && value[0] == 0 && value[1] & 0x80 == 0 {
+            return Err("integer has excess padding");
+        }
+    }
+
+    let s = &sig[len_r + 6..len_r + 6 + len_s];
+    let s = &s[s.iter().take_while(|&&byte| byte == 0).count()..];
+    if s.len() > 32 || (s.len() == 32 && s > &SECP256K1_HALF_ORDER[..]) {
+        return Err("high S");
+    }
+    Ok(())
+}
+
+/// Kind of output an input spends, as inferred from its scriptSig and witness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputType {
+    P2pk,
+    P2pkh,
+    /// P2SH or another legacy script that matched no template
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2trKeyPath,
+    P2trScriptPath,
+    NestedP2wpkh,
+    NestedP2wsh,
+    /// Witness that matched no known pattern
+    UnknownSegwit,
+    Coinbase,
+    Unknown,
+}
+
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            InputType::P2pk => "P2PK (Pay-to-Public-Key) - Legacy",
+            InputType::P2pkh => "P2PKH (Pay-to-Public-Key-Hash) - Legacy",
+            InputType::P2sh => "P2SH or Legacy",
+            InputType::P2wpkh => "P2WPKH (Pay-to-Witness-Public-Key-Hash)",
+            InputType::P2wsh => "P2WSH (Pay-to-Witness-Script-Hash)",
+            InputType::P2trKeyPath => "P2TR (Pay-to-Taproot) - Key Path Spend",
+            InputType::P2trScriptPath => "P2TR (Pay-to-Taproot) - Script Path Spend",
+            InputType::NestedP2wpkh => "P2SH-P2WPKH (Nested SegWit)",
+            InputType::NestedP2wsh => "P2SH-P2WSH (Nested SegWit)",
+            InputType::UnknownSegwit => "SegWit (Unknown type)",
+            InputType::Coinbase => "Coinbase",
+            InputType::Unknown => "Unknown",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Detect the input type based on witness data
+pub fn detect_input_type(input: &bitcoin::TxIn) -> InputType {
+    let input_type = classify_input(input);
+    debug!("detected input type {}", input_type);
+    input_type
+}
+
+impl InputType {
+    /// Short name used where space is tight, e.g. "P2SH-P2WPKH"
+    fn short_name(self) -> &'static str {
+        match self {
+            InputType::P2pk => "P2PK",
+            InputType::P2pkh => "P2PKH",
+            InputType::P2sh => "P2SH",
+            InputType::P2wpkh => "P2WPKH",
+            InputType::P2wsh => "P2WSH",
+            InputType::P2trKeyPath => "P2TR",
+            InputType::P2trScriptPath => "P2TR-script",
+            InputType::NestedP2wpkh => "P2SH-P2WPKH",
+            InputType::NestedP2wsh => "P2SH-P2WSH",
+            InputType::UnknownSegwit => "SegWit",
+            InputType::Coinbase => "Coinbase",
+            InputType::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Detect the input type from the script of the output it spends, which unlike
+/// [`detect_input_type`] doesn't have to guess from the scriptSig and witness
+pub fn detect_input_type_with_prevout(
+    input: &bitcoin::TxIn,
+    prevout: &bitcoin::TxOut,
+) -> InputType {
+    let script = &prevout.script_pubkey;
+    if script.is_p2pk() {
+        InputType::P2pk
+    } else if script.is_p2pkh() {
+        InputType::P2pkh
+    } else if script.is_p2sh() {
+        match classify_input(input) {
+            nested @ (InputType::NestedP2wpkh | InputType::NestedP2wsh) => nested,
+            _ => InputType::P2sh,
+        }
+    } else if script.is_p2wpkh() {
+        InputType::P2wpkh
+    } else if script.is_p2wsh() {
+        InputType::P2wsh
+    } else if script.is_p2tr() {
+        if taproot_control_block(input).is_some() {
+            InputType::P2trScriptPath
+        } else {
+            InputType::P2trKeyPath
+        }
+    } else if script.is_witness_program() {
+        InputType::UnknownSegwit
+    } else {
+        classify_input(input)
+    }
+}
+
+fn classify_input(input: &bitcoin::TxIn) -> InputType {
+    if input.previous_output.is_null() {
+        return InputType::Coinbase;
+    }
+
+    // Check if it's a SegWit input by examining witness data
+    if !input.witness.is_empty() {
+        // Nested SegWit: the scriptSig is a single push of the v0 witness program
+        // (the P2SH redeem script)
+        match input.script_sig.as_bytes() {
+            [0x16, 0x00, 0x14, rest @ ..] if rest.len() == 20 => {
+                return InputType::NestedP2wpkh;
+            }
+            [0x22, 0x00, 0x20, rest @ ..] if rest.len() == 32 => {
+                return InputType::NestedP2wsh;
+            }
+            _ => {}
+        }
+
+        let witness_count = input.witness.len();
+
+        // P2WPKH (Pay-to-Witness-Public-Key-Hash)
+        // Witness stack: <signature> <pubkey>
+        if witness_count == 2 {
+            let pubkey_len = input.witness.nth(1).map(|w| w.len()).unwrap_or(0);
+            if pubkey_len == 33 || pubkey_len == 65 {
+                return InputType::P2wpkh;
+            }
+        }
+
+        // P2TR script path spend: <inputs...> <script> <control block>
+        // Checked before P2WSH because control blocks are always longer than 33 bytes
+        if witness_count >= 2
+            && let Some(last_item) = input.witness.last()
+            && last_item.len() >= 33
+            && (last_item.len() - 33) % 32 == 0
+            && (last_item[0] == 0xc0 || last_item[0] == 0xc1)
+        {
+            return InputType::P2trScriptPath;
+        }
+
+        // P2WSH (Pay-to-Witness-Script-Hash)
+        // Witness stack: <item1> <item2> ... <witness_script>
+        // Last item is the actual script being satisfied
+        if witness_count >= 2 {
+            let last_item_len = input.witness.last().map(|w| w.len()).unwrap_or(0);
+            // P2WSH witness scripts are typically larger
+            if last_item_len > 33 {
+                debug!(
+                    "assuming P2WSH from the {} byte last witness item",
+                    last_item_len
+                );
+                return InputType::P2wsh;
+            }
+        }
+
+        // P2TR (Pay-to-Taproot)
+        // Key path spend: single 64-65 byte signature
+        if witness_count == 1 {
+            let sig_len = input.witness.nth(0).map(|w| w.len()).unwrap_or(0);
+            if sig_len == 64 || sig_len == 65 {
+                return InputType::P2trKeyPath;
+            }
+        }
+
+        debug!(
+            "no witness pattern matched {} items, falling back to unknown SegWit",
+            witness_count
+        );
+        return InputType::UnknownSegwit;
+    }
+
+    // Legacy input types, told apart by the pushes of the scriptSig
+    if !input.script_sig.is_empty() {
+        use bitcoin::script::Instruction;
+
+        let pushes: Option<Vec<&[u8]>> = input
+            .script_sig
+            .instructions()
+            .map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(push)) => Some(push.as_bytes()),
+                _ => None,
+            })
+            .collect();
+        let is_signature = |item: &[u8]| {
+            matches!(
+                classify_witness_item(item),
+                WitnessItemKind::EcdsaSignature { .. }
+            )
+        };
+        let is_pubkey = |item: &[u8]| {
+            matches!(
+                classify_witness_item(item),
+                WitnessItemKind::CompressedPubkey | WitnessItemKind::UncompressedPubkey
+            )
+        };
+        match pushes.as_deref() {
+            // <sig> <pubkey>
+            Some([sig, pubkey]) if is_signature(sig) && is_pubkey(pubkey) => {
+                return InputType::P2pkh;
+            }
+            // <sig>
+            Some([sig]) if is_signature(sig) => return InputType::P2pk,
+            _ => {}
+        }
+
+        // Anything else ends with a redeem script, e.g. OP_0 <sig>... <redeemScript>
+        debug!(
+            "{} byte scriptSig matched no single-key template, falling back to P2SH or Legacy",
+            input.script_sig.len()
+        );
+        return InputType::P2sh;
+    }
+
+    InputType::Unknown
+}
+
+/// Positions whose successor breaks the BIP69 ordering
+struct Bip69Violations {
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+/// Find the inputs not sorted by (txid, vout) and outputs not sorted by (value, scriptPubKey)
+fn bip69_violations(tx: &Transaction) -> Bip69Violations {
+    // BIP69 compares txids in display (reversed) byte order
+    fn input_key(input: &bitcoin::TxIn) -> ([u8; 32], u32) {
+        let mut txid = input.previous_output.txid.to_byte_array();
+        txid.reverse();
+        (txid, input.previous_output.vout)
+    }
+    fn output_key(output: &bitcoin::TxOut) -> (bitcoin::Amount, &[u8]) {
+        (output.value, output.script_pubkey.as_bytes())
+    }
+
+    Bip69Violations {
+        inputs: (1..tx.input.len())
+            .filter(|&i| input_key(&tx.input[i - 1]) > input_key(&tx.input[i]))
+            .map(|i| i - 1)
+            .collect(),
+        outputs: (1..tx.output.len())
+            .filter(|&i| output_key(&tx.output[i - 1]) > output_key(&tx.output[i]))
+            .map(|i| i - 1)
+            .collect(),
+    }
+}
+
+/// Check whether inputs and outputs follow the BIP69 lexicographic ordering
+fn is_bip69_sorted(tx: &Transaction) -> bool {
+    let violations = bip69_violations(tx);
+    violations.inputs.is_empty() && violations.outputs.is_empty()
+}
+
+/// Describe out-of-order positions, e.g. "#1 should follow #2"
+fn format_bip69_violations(positions: &[usize]) -> String {
+    positions
+        .iter()
+        .map(|i| format!("#{} should follow #{}", i, i + 1))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Largest scriptSig Bitcoin Core relays (MAX_STANDARD_SCRIPTSIG_SIZE)
+const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1650;
+
+/// Whether an input carries no signature data at all: an empty scriptSig and witness,
+/// as in a transaction that hasn't been (fully) signed yet
+fn is_unsigned(input: &bitcoin::TxIn) -> bool {
+    !input.previous_output.is_null() && input.script_sig.is_empty() && input.witness.is_empty()
+}
+
+/// Explain why an input's scriptSig is non-standard, if it is.
+/// Coinbase scriptSigs are exempt since they never enter the mempool.
+fn nonstandard_script_sig(input: &bitcoin::TxIn) -> Option<&'static str> {
+    use bitcoin::opcodes::all::OP_PUSHNUM_16;
+    use bitcoin::script::Instruction;
+
+    if input.previous_output.is_null() {
+        return None;
+    }
+    let script = &input.script_sig;
+    if script.len() > MAX_STANDARD_SCRIPTSIG_SIZE {
+        return Some("exceeds 1650 bytes");
+    }
+    // Core's IsPushOnly accepts every opcode up to OP_16
+    let push_only = script.instructions().all(|ins| match ins {
+        Ok(Instruction::PushBytes(_)) => true,
+        Ok(Instruction::Op(op)) => op.to_u8() <= OP_PUSHNUM_16.to_u8(),
+        Err(_) => false,
+    });
+    (!push_only).then_some("contains non-push opcodes")
+}
+
+/// Largest transaction weight Bitcoin Core relays (MAX_STANDARD_TX_WEIGHT)
+const MAX_STANDARD_TX_WEIGHT: u64 = 400_000;
+
+/// Largest serialized size Bitcoin Core relayed before SegWit, i.e. before 0.13 (the former
+/// MAX_STANDARD_TX_SIZE). The weight limit replaced it and nothing enforces it anymore.
+const MAX_STANDARD_TX_SIZE: usize = 100_000;
+
+/// Largest signature operation cost Bitcoin Core relays in one transaction
+/// (MAX_STANDARD_TX_SIGOPS_COST)
+const MAX_STANDARD_TX_SIGOPS_COST: usize = 16_000;
+
+/// Legacy sigops weigh this much more than witness sigops (WITNESS_SCALE_FACTOR)
+const WITNESS_SCALE_FACTOR: usize = 4;
+
+/// Signature operation cost of the transaction, as Bitcoin Core's
+/// `GetTransactionSigOpCost` computes it: the sigops of every scriptSig and scriptPubKey
+/// with legacy counting (OP_CHECKSIG 1, OP_CHECKMULTISIG 20) weigh 4 each, and the witness
+/// sigops of P2WPKH spends (1) and P2WSH witness scripts (counted accurately) weigh 1.
+/// P2SH redeem scripts and the remaining witness sigops depend on the prevout scripts, so
+/// they are left out.
+fn sigop_cost(tx: &Transaction) -> usize {
+    let legacy: usize = tx
+        .input
+        .iter()
+        .map(|input| input.script_sig.count_sigops_legacy())
+        .chain(
+            tx.output
+                .iter()
+                .map(|output| output.script_pubkey.count_sigops_legacy()),
+        )
+        .sum();
+    let witness: usize = tx
+        .input
+        .iter()
+        .map(|input| match classify_input(input) {
+            InputType::P2wpkh | InputType::NestedP2wpkh => 1,
+            InputType::P2wsh | InputType::NestedP2wsh => input.witness.last().map_or(0, |script| {
+                bitcoin::Script::from_bytes(script).count_sigops()
+            }),
+            _ => 0,
+        })
+        .sum();
+    legacy * WITNESS_SCALE_FACTOR + witness
+}
+
+/// Relay policy (not consensus) limits the transaction breaks
+fn policy_warnings(tx: &Transaction) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let weight = tx.weight().to_wu();
+    if weight > MAX_STANDARD_TX_WEIGHT {
+        warnings.push(format!(
+            "exceeds standard weight limit: {} WU > {} WU (policy MAX_STANDARD_TX_WEIGHT, not consensus)",
+            weight, MAX_STANDARD_TX_WEIGHT
+        ));
+    }
+    let size = tx.total_size();
+    if size > MAX_STANDARD_TX_SIZE {
+        warnings.push(format!(
+            "exceeds legacy size limit: {} bytes > {} bytes (pre-SegWit MAX_STANDARD_TX_SIZE, not enforced since Bitcoin Core 0.13)",
+            size, MAX_STANDARD_TX_SIZE
+        ));
+    }
+    let cost = sigop_cost(tx);
+    if cost > MAX_STANDARD_TX_SIGOPS_COST {
+        warnings.push(format!(
+            "exceeds standard sigop cost limit: {} > {} (policy MAX_STANDARD_TX_SIGOPS_COST, not consensus)",
+            cost, MAX_STANDARD_TX_SIGOPS_COST
+        ));
+    }
+    // The size of each OP_RETURN is checked by `is_standard`
+    let op_returns = tx
+        .output
+        .iter()
+        .filter(|output| output.script_pubkey.is_op_return())
+        .count();
+    if op_returns > 1 {
+        warnings.push(format!(
+            "non-standard: {} OP_RETURN outputs, default policy allows 1 (Bitcoin Core 30+ relays several)",
+            op_returns
+        ));
+    }
+    warnings
+}
+
+/// Likely construction mistakes that are neither consensus nor policy violations
+fn sanity_warnings(tx: &Transaction) -> Vec<String> {
+    let mut warnings: Vec<String> = tx
+        .output
+        .iter()
+        .enumerate()
+        // Zero-value data carriers and P2A anchors are legitimate
+        .filter(|(_, output)| {
+            output.value.to_sat() == 0
+                && !output.script_pubkey.is_op_return()
+                && !is_ephemeral_anchor(output)
+        })
+        .map(|(idx, _)| format!("zero-value non-data output (output {})", idx))
+        .collect();
+    for (first, second) in duplicate_outputs(tx) {
+        warnings.push(format!(
+            "outputs {} and {} are identical (same scriptPubKey and value)",
+            first, second
+        ));
+    }
+    warnings
+}
+
+/// Pairs of outputs with the same scriptPubKey and value, each later duplicate
+/// paired with the first output it repeats
+fn duplicate_outputs(tx: &Transaction) -> Vec<(usize, usize)> {
+    let mut first_seen = std::collections::HashMap::new();
+    let mut pairs = Vec::new();
+    for (idx, output) in tx.output.iter().enumerate() {
+        let first = *first_seen.entry(output).or_insert(idx);
+        if first != idx {
+            pairs.push((first, idx));
+        }
+    }
+    pairs
+}
+
+/// Largest OP_RETURN scriptPubKey Bitcoin Core relays by default (-datacarriersize plus overhead)
+const MAX_OP_RETURN_RELAY: usize = 83;
+
+/// Apply Bitcoin Core's standardness policy, returning whether the transaction
+/// passes and the reasons it doesn't
+fn is_standard(tx: &Transaction) -> (bool, Vec<String>) {
+    let mut reasons = Vec::new();
+
+    if !(1..=3).contains(&tx.version.0) {
+        reasons.push(format!("version {} is non-standard", tx.version.0));
+    }
+    let weight = tx.weight().to_wu();
+    if weight > MAX_STANDARD_TX_WEIGHT {
+        reasons.push(format!(
+            "weight {} WU exceeds {} WU",
+            weight, MAX_STANDARD_TX_WEIGHT
+        ));
+    }
+
+    for (idx, input) in tx.input.iter().enumerate() {
+        if let Some(reason) = nonstandard_script_sig(input) {
+            reasons.push(format!("input {} scriptSig {}", idx, reason));
+        }
+    }
+
+    let mut op_returns = 0;
+    for (idx, output) in tx.output.iter().enumerate() {
+        let script = output.script_pubkey.as_bytes();
+        match core_script_type(output) {
+            "nonstandard" => reasons.push(format!("output {} pays to a non-standard script", idx)),
+            "multisig" => {
+                let (_, keys) = multisig_threshold(&output.script_pubkey).unwrap_or_default();
+                if keys > 3 {
+                    reasons.push(format!(
+                        "output {} is bare multisig with {} keys (max 3)",
+                        idx, keys
+                    ));
+                }
+            }
+            "nulldata" => {
+                op_returns += 1;
+                if script.len() > MAX_OP_RETURN_RELAY {
+                    reasons.push(format!(
+                        "output {} OP_RETURN is {} bytes (max {}, i.e. 80 bytes of data; Bitcoin Core 30+ relays larger data carriers by default)",
+                        idx,
+                        script.len(),
+                        MAX_OP_RETURN_RELAY
+                    ));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        let dust = output.script_pubkey.minimal_non_dust();
+        if output.value < dust {
+            reasons.push(format!(
+                "output {} is dust ({} < {} satoshis)",
+                idx,
+                output.value.to_sat(),
+                dust.to_sat()
+            ));
+        }
+    }
+    if op_returns > 1 {
+        reasons.push(format!("{} OP_RETURN outputs (max 1)", op_returns));
+    }
+
+    (reasons.is_empty(), reasons)
+}
+
+/// Compare the transaction's ids with the expected ones. A matching txid with a different
+/// wtxid means the witness was changed, which doesn't change the txid.
+fn id_checks(tx: &Transaction, opts: &RenderOptions) -> Vec<(&'static str, String, bool)> {
+    let txid = tx.compute_txid();
+    let wtxid = tx.compute_wtxid();
+    let mut checks = Vec::new();
+    if let Some(expected) = opts.expected_txid {
+        checks.push(if expected == txid {
+            ("Expected txid", "✓ matches".to_string(), true)
+        } else {
+            (
+                "Expected txid",
+                format!("✗ mismatch, expected {}", expected),
+                false,
+            )
+        });
+    }
+    if let Some(expected) = opts.expected_wtxid {
+        checks.push(if expected == wtxid {
+            ("Expected wtxid", "✓ matches".to_string(), true)
+        } else if opts.expected_txid == Some(txid) {
+            (
+                "Expected wtxid",
+                format!(
+                    "⚠ witness malleation detected: same txid, expected wtxid {}",
+                    expected
+                ),
+                false,
+            )
+        } else {
+            (
+                "Expected wtxid",
+                format!("✗ mismatch, expected {}", expected),
+                false,
+            )
+        });
+    }
+    checks
+}
+
+/// Block height committed to by a coinbase transaction: the number pushed first in
+/// its scriptSig, minimally encoded as a script number (BIP34)
+pub fn bip34_height(tx: &Transaction) -> Result<u64, String> {
+    use bitcoin::script::Instruction;
+
+    if !tx.is_coinbase() {
+        return Err("not a coinbase transaction".to_string());
+    }
+    let script_sig = &tx.input[0].script_sig;
+    match script_sig.instructions_minimal().next() {
+        Some(Ok(Instruction::PushBytes(push))) => {
+            let height = bitcoin::script::read_scriptint(push.as_bytes()).map_err(|_| {
+                format!(
+                    "leading push {} is not a minimally encoded number",
+                    hex::encode(push.as_bytes())
+                )
+            })?;
+            u64::try_from(height).map_err(|_| format!("height {} is negative", height))
+        }
+        // Heights 1 to 16 are pushed with OP_1..OP_16
+        Some(Ok(Instruction::Op(op))) if (0x51..=0x60).contains(&op.to_u8()) => {
+            Ok(u64::from(op.to_u8() - 0x50))
+        }
+        _ => Err("scriptSig doesn't start with a height push".to_string()),
+    }
+}
+
+/// Maximum virtual size of a TRUC transaction under Bitcoin Core v28 policy
+const TRUC_MAX_VSIZE: usize = 10_000;
+
+/// Check whether a transaction opts in to the TRUC (version 3) relay policy
+fn is_truc(tx: &Transaction) -> bool {
+    tx.version.0 == 3
+}
+
+/// Check whether nLockTime is enforced, which requires an input with a non-final sequence
+fn is_lock_time_active(tx: &Transaction) -> bool {
+    tx.input
+        .iter()
+        .any(|input| input.sequence.enables_absolute_lock_time())
+}
+
+/// Describe whether a lock time is a block height or a Unix timestamp
+fn describe_lock_time(lock_time: bitcoin::absolute::LockTime) -> String {
+    use bitcoin::absolute::LockTime;
+
+    match lock_time {
+        LockTime::Blocks(height) => format!("block height {}", height),
+        LockTime::Seconds(time) => format!("Unix time {}", time),
+    }
+}
+
+/// Script an input reveals when spending: tapscript leaf, witness script or P2SH redeem script
+fn revealed_script(input: &bitcoin::TxIn) -> Option<&bitcoin::Script> {
+    use bitcoin::script::Instruction;
+
+    if let Some(leaf) = input.witness.taproot_leaf_script()
+        && leaf.version == bitcoin::taproot::LeafVersion::TapScript
+    {
+        return Some(leaf.script);
+    }
+    if input.witness.len() >= 2 {
+        return input.witness.last().map(bitcoin::Script::from_bytes);
+    }
+    if input.witness.is_empty() {
+        return match input.script_sig.instructions().last()? {
+            Ok(Instruction::PushBytes(push)) if !push.is_empty() => {
+                Some(bitcoin::Script::from_bytes(push.as_bytes()))
+            }
+            _ => None,
+        };
+    }
+    None
+}
+
+/// `(m, n)` of an `OP_m <n pubkeys> OP_n OP_CHECKMULTISIG` script
+fn multisig_threshold(script: &bitcoin::Script) -> Option<(u8, u8)> {
+    use bitcoin::script::Instruction;
+
+    if !script.is_multisig() {
+        return None;
+    }
+    // `is_multisig` guarantees the script starts with OP_1 through OP_16
+    let m = script.as_bytes()[0] - 0x50;
+    let n = script
+        .instructions()
+        .skip(1)
+        .filter_map(Result::ok)
+        .take_while(|instruction| matches!(instruction, Instruction::PushBytes(_)))
+        .count();
+    Some((m, n as u8))
+}
+
+/// A legacy P2SH multisig spend: `OP_0 <sig>... <redeemScript>`
+#[derive(Debug, PartialEq)]
+struct P2shMultisig {
+    redeem_script: bitcoin::ScriptBuf,
+    required: u8,
+    keys: u8,
+    signatures: usize,
+}
+
+/// Split a P2SH multisig scriptSig into its signatures and redeem script. The leading
+/// OP_0 is the dummy element CHECKMULTISIG pops because of an off-by-one bug, not a
+/// signature, so it isn't counted.
+fn p2sh_multisig(input: &bitcoin::TxIn) -> Option<P2shMultisig> {
+    use bitcoin::script::Instruction;
+
+    if !input.witness.is_empty() {
+        return None;
+    }
+    let pushes = input
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(push)) => Some(push.as_bytes()),
+            _ => None,
+        })
+        .collect::<Option<Vec<&[u8]>>>()?;
+    let [dummy, signatures @ .., redeem_script] = pushes.as_slice() else {
+        return None;
+    };
+    if !dummy.is_empty() {
+        return None;
+    }
+    let redeem_script = bitcoin::ScriptBuf::from_bytes(redeem_script.to_vec());
+    let (required, keys) = multisig_threshold(&redeem_script)?;
+    Some(P2shMultisig {
+        redeem_script,
+        required,
+        keys,
+        signatures: signatures.len(),
+    })
+}
+
+/// Describe the CLTV and CSV timelocks a script enforces, from the push preceding each opcode
+fn script_timelocks(script: &bitcoin::Script) -> Vec<String> {
+    use bitcoin::opcodes::all::{OP_CLTV, OP_CSV};
+    use bitcoin::relative;
+    use bitcoin::script::Instruction;
+
+    let Ok(instructions) = script.instructions().collect::<Result<Vec<_>, _>>() else {
+        return Vec::new();
+    };
+    instructions
+        .windows(2)
+        .filter_map(|window| {
+            let value = u32::try_from(window[0].script_num()?).ok()?;
+            match window[1] {
+                Instruction::Op(OP_CLTV) => Some(format!(
+                    "CLTV {}",
+                    describe_lock_time(bitcoin::absolute::LockTime::from_consensus(value))
+                )),
+                Instruction::Op(OP_CSV) => Some(match relative::LockTime::from_consensus(value) {
+                    Ok(relative::LockTime::Blocks(height)) => {
+                        format!("CSV {} blocks", height.value())
+                    }
+                    Ok(relative::LockTime::Time(time)) => {
+                        format!("CSV {} seconds", u32::from(time.value()) * 512)
+                    }
+                    Err(_) => format!("CSV {} (disabled)", value),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Sighash types of the signatures found in an input's scriptSig and witness.
+/// Schnorr signatures are only looked for in Taproot spends, where a 64-byte
+/// signature means SIGHASH_DEFAULT (reported as SIGHASH_ALL, which it commits like).
+fn input_sighash_types(input: &bitcoin::TxIn) -> Vec<EcdsaSighashType> {
+    let mut types = Vec::new();
+
+    let pushes = input
+        .script_sig
+        .instructions()
+        .filter_map(|instruction| match instruction {
+            Ok(bitcoin::script::Instruction::PushBytes(push)) => Some(push.as_bytes()),
+            _ => None,
+        });
+    for item in pushes.chain(input.witness.iter()) {
+        if let Ok(signature) = bitcoin::ecdsa::Signature::from_slice(item) {
+            types.push(signature.sighash_type);
+        }
+    }
+
+    let signatures = match classify_input(input) {
+        InputType::P2trKeyPath => input.witness.len().min(1),
+        // The last two items are the leaf script and the control block
+        InputType::P2trScriptPath => input.witness.len() - 2,
+        _ => 0,
+    };
+    for item in input.witness.iter().take(signatures) {
+        if let Ok(signature) = bitcoin::taproot::Signature::from_slice(item) {
+            types.push(match signature.sighash_type {
+                TapSighashType::Default | TapSighashType::All => EcdsaSighashType::All,
+                TapSighashType::None => EcdsaSighashType::None,
+                TapSighashType::Single => EcdsaSighashType::Single,
+                TapSighashType::AllPlusAnyoneCanPay => EcdsaSighashType::AllPlusAnyoneCanPay,
+                TapSighashType::NonePlusAnyoneCanPay => EcdsaSighashType::NonePlusAnyoneCanPay,
+                TapSighashType::SinglePlusAnyoneCanPay => EcdsaSighashType::SinglePlusAnyoneCanPay,
+            });
+        }
+    }
+    types
+}
+
+/// Privacy and safety notes about the sighash types used across the inputs
+fn sighash_warnings(tx: &Transaction, per_input: &[Vec<EcdsaSighashType>]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let mut distinct: Vec<EcdsaSighashType> = per_input.iter().flatten().copied().collect();
+    distinct.sort_by_key(|sighash_type| sighash_type.to_u32());
+    distinct.dedup();
+    if distinct.len() > 1 {
+        warnings.push("mixed sighash types detected".to_string());
+    }
+
+    for idx in (0..tx.input.len()).filter(|&idx| has_sighash_single_bug(tx, idx)) {
+        warnings.push(sighash_single_bug_warning(idx));
+    }
+    warnings
+}
+
+/// Whether a legacy input signs with SIGHASH_SINGLE although there is no output at its
+/// index. Legacy signature hashing then signs the value 1 instead of failing, so the
+/// signature can be replayed on any transaction spending the same output. SegWit
+/// signature hashing (BIP143 and BIP341) doesn't have the bug.
+fn has_sighash_single_bug(tx: &Transaction, idx: usize) -> bool {
+    let Some(input) = tx.input.get(idx) else {
+        return false;
+    };
+    if idx < tx.output.len() || !input.witness.is_empty() {
+        return false;
+    }
+    input.script_sig.instructions().any(|instruction| {
+        let Ok(bitcoin::script::Instruction::PushBytes(push)) = instruction else {
+            return false;
+        };
+        bitcoin::ecdsa::Signature::from_slice(push.as_bytes()).is_ok_and(|signature| {
+            matches!(
+                signature.sighash_type,
+                EcdsaSighashType::Single | EcdsaSighashType::SinglePlusAnyoneCanPay
+            )
+        })
+    })
+}
+
+/// Warning shown for an input with the SIGHASH_SINGLE bug
+fn sighash_single_bug_warning(idx: usize) -> String {
+    format!(
+        "SIGHASH_SINGLE bug: input #{} has no corresponding output",
+        idx
+    )
+}
+
+/// Values that are a multiple of this many satoshis (0.001 BTC) look like payments
+const ROUND_VALUE_SATS: u64 = 100_000;
+
+/// Output script kind an input spends, for comparing with the outputs' kinds
+fn spent_script_kind(input_type: InputType) -> Option<&'static str> {
+    match input_type {
+        InputType::P2pk => Some("pubkey"),
+        InputType::P2pkh => Some("pubkeyhash"),
+        InputType::P2sh | InputType::NestedP2wpkh | InputType::NestedP2wsh => Some("scripthash"),
+        InputType::P2wpkh => Some("witness_v0_keyhash"),
+        InputType::P2wsh => Some("witness_v0_scripthash"),
+        InputType::P2trKeyPath | InputType::P2trScriptPath => Some("witness_v1_taproot"),
+        _ => None,
+    }
+}
+
+/// Short name of an output's script type, matching [`InputType`]'s short names
+fn output_short_name(output: &bitcoin::TxOut) -> &'static str {
+    match core_script_type(output) {
+        "pubkey" => "P2PK",
+        "pubkeyhash" => "P2PKH",
+        "scripthash" => "P2SH",
+        "multisig" => "Multisig",
+        "witness_v0_keyhash" => "P2WPKH",
+        "witness_v0_scripthash" => "P2WSH",
+        "witness_v1_taproot" => "P2TR",
+        "anchor" => "P2A",
+        "witness_unknown" => "SegWit",
+        "nulldata" => "OP_RETURN",
+        _ => "Nonstandard",
+    }
+}
+
+/// One-line count of the input and output types, e.g. "1×P2WPKH → 2×P2WPKH, 1×P2TR".
+/// Types are listed in order of first appearance.
+fn type_summary(tx: &Transaction, opts: &RenderOptions) -> String {
+    fn count<'a>(names: impl Iterator<Item = &'a str>) -> String {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for name in names {
+            match counts.iter_mut().find(|(seen, _)| *seen == name) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+        counts
+            .iter()
+            .map(|(name, n)| format!("{}×{}", n, name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    let inputs = tx.input.iter().enumerate().map(|(idx, input)| {
+        match opts.spent_outputs.get(idx).and_then(Option::as_ref) {
+            Some(prevout) => detect_input_type_with_prevout(input, prevout),
+            None => detect_input_type(input),
+        }
+        .short_name()
+    });
+    format!(
+        "{} → {}",
+        count(inputs),
+        count(tx.output.iter().map(output_short_name))
+    )
+}
+
+/// Upper bounds in satoshis (exclusive) and labels of the output value histogram buckets
+const HISTOGRAM_BUCKETS: &[(u64, &str)] = &[
+    (1, "0 BTC"),
+    (100_000, "< 0.001 BTC"),
+    (1_000_000, "< 0.01 BTC"),
+    (10_000_000, "< 0.1 BTC"),
+    (100_000_000, "< 1 BTC"),
+    (1_000_000_000, "< 10 BTC"),
+    (u64::MAX, "10+ BTC"),
+];
+
+/// Number of outputs in each value bucket, by order of magnitude
+fn value_histogram(tx: &Transaction) -> Vec<(&'static str, usize)> {
+    let mut counts = vec![0; HISTOGRAM_BUCKETS.len()];
+    for output in &tx.output {
+        let value = output.value.to_sat();
+        let bucket = HISTOGRAM_BUCKETS
+            .iter()
+            .position(|&(bound, _)| value < bound)
+            .unwrap_or(HISTOGRAM_BUCKETS.len() - 1);
+        counts[bucket] += 1;
+    }
+    HISTOGRAM_BUCKETS
+        .iter()
+        .zip(counts)
+        .map(|(&(_, label), count)| (label, count))
+        .collect()
+}
+
+/// Guess which output returns change to the spender. Advisory only: prefers the single
+/// output whose script type matches a spent input's type, then the single output with a
+/// non-round value next to round ones (payments tend to be round amounts).
+pub fn likely_change_output(tx: &Transaction) -> Option<usize> {
+    if tx.is_coinbase() || tx.output.len() < 2 {
+        return None;
+    }
+    let spender_kinds: Vec<&str> = tx
+        .input
+        .iter()
+        .filter_map(|input| spent_script_kind(classify_input(input)))
+        .collect();
+    let candidates: Vec<usize> = (0..tx.output.len())
+        .filter(|&idx| !tx.output[idx].script_pubkey.is_op_return())
+        .collect();
+
+    let matching: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&idx| spender_kinds.contains(&core_script_type(&tx.output[idx])))
+        .collect();
+    if let [change] = matching.as_slice() {
+        return Some(*change);
+    }
+
+    let pool = if matching.is_empty() {
+        &candidates
+    } else {
+        &matching
+    };
+    let is_round = |idx: &usize| {
+        tx.output[*idx]
+            .value
+            .to_sat()
+            .is_multiple_of(ROUND_VALUE_SATS)
+    };
+    let non_round: Vec<usize> = pool.iter().copied().filter(|idx| !is_round(idx)).collect();
+    match non_round.as_slice() {
+        [change] if candidates.iter().any(is_round) => Some(*change),
+        _ => None,
+    }
+}
+
+/// Check whether a transaction opts in to replacement under BIP125
+/// (replaceable if any input sequence < 0xfffffffe)
+fn is_replaceable(tx: &Transaction) -> bool {
+    tx.input.iter().any(|input| input.sequence.is_rbf())
+}
+
+/// BIP68 fields of a raw nSequence: the disable flag (bit 31), the type flag (bit 22,
+/// set for 512-second units) and the 16-bit value, plus any other bits that are set
+fn sequence_breakdown(sequence: bitcoin::Sequence) -> String {
+    const DISABLE_FLAG: u32 = 1 << 31;
+    const TYPE_FLAG: u32 = 1 << 22;
+    const VALUE_MASK: u32 = 0xffff;
+
+    let raw = sequence.to_consensus_u32();
+    let disabled = raw & DISABLE_FLAG != 0;
+    let time = raw & TYPE_FLAG != 0;
+    let value = raw & VALUE_MASK;
+    let mut breakdown = format!(
+        "disable={}, type={}, value={}",
+        disabled,
+        if time { "time" } else { "blocks" },
+        value
+    );
+    if disabled {
+        breakdown.push_str(" (no relative timelock)");
+    } else if time {
+        breakdown.push_str(&format!(" ({}s)", value * 512));
+    } else {
+        breakdown.push_str(&format!(" ({})", plural(value as usize, "block")));
+    }
+    let reserved = raw & !(DISABLE_FLAG | TYPE_FLAG | VALUE_MASK);
+    if reserved != 0 {
+        breakdown.push_str(&format!(", reserved bits 0x{:08x} (ignored)", reserved));
+    }
+    breakdown
+}
+
+/// Whether the transaction can be replaced in the mempool, distinguishing BIP125
+/// opt-in from replacement that relies on a full-RBF node policy
+fn replaceability(tx: &Transaction, full_rbf: bool) -> &'static str {
+    if tx.is_coinbase() {
+        "Not replaceable: coinbase transactions never enter the mempool"
+    } else if is_replaceable(tx) {
+        "Replaceable: opt-in (BIP125)"
+    } else if full_rbf {
+        "Replaceable: no BIP125 signal, but full-RBF nodes replace it anyway"
+    } else {
+        "Replaceable: only under full-RBF policy (no BIP125 signal)"
+    }
+}
+
+/// Address an output pays to, along with whether it maps back to the same script
+struct OutputAddress {
+    address: bitcoin::Address,
+    /// True if `address.script_pubkey()` reproduces the output's script bytes
+    round_trips: bool,
+}
+
+/// Derive the address an output pays to on the given network, if it has one
+fn output_address(output: &bitcoin::TxOut, network: bitcoin::Network) -> Option<OutputAddress> {
+    let address = bitcoin::Address::from_script(&output.script_pubkey, network).ok()?;
+    let round_trips = address.script_pubkey() == output.script_pubkey;
+    Some(OutputAddress {
+        address,
+        round_trips,
+    })
+}
+
+/// P2PKH address of the key locked by a bare P2PK output. P2PK has no address of its
+/// own, but wallets of the era tracked these coins under the key's P2PKH address.
+fn p2pk_address(output: &bitcoin::TxOut, network: Network) -> Option<bitcoin::Address> {
+    let pubkey = output.script_pubkey.p2pk_public_key()?;
+    Some(bitcoin::Address::p2pkh(pubkey, network))
+}
+
+/// Electrum server index key: sha256 of the scriptPubKey, hex-encoded in reversed byte order
+fn electrum_scripthash(output: &bitcoin::TxOut) -> String {
+    let mut hash =
+        bitcoin::hashes::sha256::Hash::hash(output.script_pubkey.as_bytes()).to_byte_array();
+    hash.reverse();
+    hex::encode(hash)
+}
+
+/// Every public key the transaction reveals: pushes in scriptSigs and scriptPubKeys
+/// (P2PK, bare multisig) and witness items, in order of first appearance. Only items
+/// that parse as secp256k1 points are returned.
+pub fn extract_pubkeys(tx: &Transaction) -> Vec<bitcoin::PublicKey> {
+    let pushes = |script: &bitcoin::Script| -> Vec<Vec<u8>> {
+        script
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(bitcoin::script::Instruction::PushBytes(data)) => Some(data.as_bytes().to_vec()),
+                _ => None,
+            })
+            .collect()
+    };
+    let candidates = tx
+        .input
+        .iter()
+        .flat_map(|input| {
+            pushes(&input.script_sig)
+                .into_iter()
+                .chain(input.witness.iter().map(<[u8]>::to_vec))
+        })
+        .chain(
+            tx.output
+                .iter()
+                .flat_map(|output| pushes(&output.script_pubkey)),
+        );
+
+    let mut keys = Vec::new();
+    for candidate in candidates {
+        if !matches!(candidate.len(), 33 | 65) {
+            continue;
+        }
+        if let Ok(key) = bitcoin::PublicKey::from_slice(&candidate)
+            && !keys.contains(&key)
+        {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Every x-only Taproot key the transaction reveals: internal keys from script path
+/// control blocks and output keys of P2TR outputs, in order of first appearance.
+/// Only valid points are returned.
+pub fn extract_xonly_keys(tx: &Transaction) -> Vec<bitcoin::XOnlyPublicKey> {
+    let internal_keys = tx
+        .input
+        .iter()
+        .filter_map(|input| Some(taproot_control_block(input)?.internal_key));
+    let output_keys = tx.output.iter().filter_map(taproot_output_key);
+
+    let mut keys = Vec::new();
+    for key in internal_keys.chain(output_keys) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Check whether an output pays to the given address on `network`. Addresses that
+/// don't parse or belong to another network never match.
+pub fn output_pays_to(output: &bitcoin::TxOut, address: &str, network: Network) -> bool {
+    address
+        .parse::<bitcoin::Address<_>>()
+        .ok()
+        .and_then(|address| address.require_network(network).ok())
+        .is_some_and(|address| address.script_pubkey() == output.script_pubkey)
+}
+
+/// Get the address type as a human-readable string
+fn get_address_type(address: &bitcoin::Address) -> &'static str {
+    use bitcoin::address::AddressType;
+
+    match address.address_type() {
+        Some(AddressType::P2pkh) => "P2PKH",
+        Some(AddressType::P2sh) => "P2SH",
+        Some(AddressType::P2wpkh) => "P2WPKH",
+        Some(AddressType::P2wsh) => "P2WSH",
+        Some(AddressType::P2tr) => "P2TR",
+        Some(AddressType::P2a) => "P2A",
+        _ => "Unknown",
+    }
+}
+
+/// Transaction in the shape of Bitcoin Core's `decoderawtransaction` result
+#[derive(Debug, Serialize)]
+pub struct CoreTransaction {
+    txid: String,
+    hash: String,
+    version: i32,
+    size: usize,
+    vsize: usize,
+    weight: u64,
+    locktime: u32,
+    vin: Vec<CoreInput>,
+    vout: Vec<CoreOutput>,
+}
+
+#[derive(Debug, Serialize)]
+struct CoreInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coinbase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vout: Option<u32>,
+    #[serde(rename = "scriptSig", skip_serializing_if = "Option::is_none")]
+    script_sig: Option<CoreScriptSig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    txinwitness: Option<Vec<String>>,
+    sequence: u32,
+}
+
+/// Write an amount in BTC with all 8 decimals, e.g. `0.00000001`, like `bitcoin-cli` does.
+/// A float would come out as `1e-8` in JSON, so human-readable formats get the digits as
+/// a raw JSON number; binary formats such as MessagePack get a float.
+fn serialize_btc<S: serde::Serializer>(
+    amount: &bitcoin::Amount,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serializer.serialize_f64(amount.to_btc());
+    }
+    const SATS_PER_BTC: u64 = 100_000_000;
+    let sats = amount.to_sat();
+    let digits = format!("{}.{:08}", sats / SATS_PER_BTC, sats % SATS_PER_BTC);
+    serde_json::value::RawValue::from_string(digits)
+        .expect("a decimal number is valid JSON")
+        .serialize(serializer)
+}
+
+#[derive(Debug, Serialize)]
+struct CoreScriptSig {
+    asm: String,
+    hex: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CoreOutput {
+    #[serde(serialize_with = "serialize_btc")]
+    value: bitcoin::Amount,
+    n: usize,
+    #[serde(rename = "scriptPubKey")]
+    script_pubkey: CoreScriptPubKey,
+}
+
+#[derive(Debug, Serialize)]
+struct CoreScriptPubKey {
+    asm: String,
+    hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(rename = "type")]
+    script_type: &'static str,
+}
+
+/// A named output template, recognized by its `matches` function
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptPattern {
+    /// Name reported for matching outputs
+    pub name: &'static str,
+    /// Whether an output follows the template
+    pub matches: fn(&bitcoin::TxOut) -> bool,
+}
+
+/// Templates recognized beyond the address types, named after Core's `TxoutType`
+const BUILTIN_PATTERNS: &[ScriptPattern] = &[
+    ScriptPattern {
+        name: "multisig",
+        matches: |output| output.script_pubkey.is_multisig(),
+    },
+    ScriptPattern {
+        name: "anchor",
+        matches: is_ephemeral_anchor,
+    },
+    ScriptPattern {
+        name: "nulldata",
+        matches: |output| output.script_pubkey.is_op_return(),
+    },
+];
+
+/// Classify a scriptPubKey with Bitcoin Core's `TxoutType` names
+fn core_script_type(output: &bitcoin::TxOut) -> &'static str {
+    let script = &output.script_pubkey;
+    if script.is_p2pk() {
+        "pubkey"
+    } else if script.is_p2pkh() {
+        "pubkeyhash"
+    } else if script.is_p2sh() {
+        "scripthash"
+    } else if script.is_p2wpkh() {
+        "witness_v0_keyhash"
+    } else if script.is_p2wsh() {
+        "witness_v0_scripthash"
+    } else if script.is_p2tr() {
+        "witness_v1_taproot"
+    } else if let Some(pattern) = BUILTIN_PATTERNS.iter().find(|p| (p.matches)(output)) {
+        pattern.name
+    } else if script.is_witness_program() {
+        "witness_unknown"
+    } else {
+        "nonstandard"
+    }
+}
+
+/// Name of the template an output follows: the first matching pattern registered in
+/// `opts`, otherwise Bitcoin Core's `TxoutType` name, e.g. "witness_v0_keyhash"
+pub fn classify_output(output: &bitcoin::TxOut, opts: &RenderOptions) -> &'static str {
+    opts.script_patterns
+        .iter()
+        .find(|pattern| (pattern.matches)(output))
+        .map_or_else(|| core_script_type(output), |pattern| pattern.name)
+}
+
+/// Build the `decoderawtransaction`-compatible view of a transaction
+pub fn core_decode(tx: &Transaction, network: Network) -> CoreTransaction {
+    let vin = tx
+        .input
+        .iter()
+        .map(|input| {
+            let script_hex = hex::encode(input.script_sig.as_bytes());
+            let txinwitness = (!input.witness.is_empty())
+                .then(|| input.witness.iter().map(hex::encode).collect());
+            if input.previous_output.is_null() {
+                CoreInput {
+                    coinbase: Some(script_hex),
+                    txid: None,
+                    vout: None,
+                    script_sig: None,
+                    txinwitness,
+                    sequence: input.sequence.0,
+                }
+            } else {
+                CoreInput {
+                    coinbase: None,
+                    txid: Some(input.previous_output.txid.to_string()),
+                    vout: Some(input.previous_output.vout),
+                    script_sig: Some(CoreScriptSig {
+                        asm: input.script_sig.to_asm_string(),
+                        hex: script_hex,
+                    }),
+                    txinwitness,
+                    sequence: input.sequence.0,
+                }
+            }
+        })
+        .collect();
+
+    let vout = tx
+        .output
+        .iter()
+        .enumerate()
+        .map(|(n, output)| CoreOutput {
+            value: output.value,
+            n,
+            script_pubkey: CoreScriptPubKey {
+                asm: output.script_pubkey.to_asm_string(),
+                hex: hex::encode(output.script_pubkey.as_bytes()),
+                address: output_address(output, network).map(|a| a.address.to_string()),
+                script_type: core_script_type(output),
+            },
+        })
+        .collect();
+
+    CoreTransaction {
+        txid: tx.compute_txid().to_string(),
+        hash: tx.compute_wtxid().to_string(),
+        version: tx.version.0,
+        size: tx.total_size(),
+        vsize: tx.vsize(),
+        weight: tx.weight().to_wu(),
+        locktime: tx.lock_time.to_consensus_u32(),
+        vin,
+        vout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // P2WPKH segwit transaction with 1 input and 3 outputs
+    const SEGWIT_TX_HEX: &str = "020000000001010eeb61beeddeaab8a7bb024efcac1fa3faecb7c96c4127782e6bc7cd59fc51490200000000fffffffd03afd701000000000017a914715a091837e1340c8f4d11c20a16a4c92cee9af187ce22000000000000225120a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208aef780800000000001600148db324a5c4bf820717091087769dee302809ccb202483045022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f3140220283339f2abbd165bbc6b4b305db28b9a064f051c2b097f318c2fa507bf320bc3012103cc976f202ab9e2d0bbe3ed8e728aadd6042294f223ff665516c114733647b6ba00000000";
+
+    // P2SH-P2WPKH spend with 1 input and 1 output, from rust-bitcoin's test vectors
+    const NESTED_SEGWIT_TX_HEX: &str = "0200000000010166c3d39490dc827a2594c7b17b7d37445e1f4b372179649cd2ce4475e3641bbb0100000017160014e69aa750e9bff1aca1e32e57328b641b611fc817fdffffff01e87c5d010000000017a914f3890da1b99e44cd3d52f7bcea6a1351658ea7be87024830450221009eb97597953dc288de30060ba02d4e91b2bde1af2ecf679c7f5ab5989549aa8002202a98f8c3bd1a5a31c0d72950dd6e2e3870c6c5819a6c3db740e91ebbbc5ef4800121023f3d3b8e74b807e32217dea2c75c8d0bd46b8665b3a2d9b3cb310959de52a09bc9d20700";
+
+    // Legacy P2PKH transaction with 1 input and 1 output
+    const LEGACY_TX_HEX: &str = "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000";
+
+    // Coinbase segwit transaction with 1 input and 2 outputs
+    const COINBASE_TX_HEX: &str = "020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff0502e8030101ffffffff0200f2052a0100000016001496d5599e55dfb1d6a2adc94e4f7e3b0f6b3b6b100000000000000000266a24aa21a9ede2f61c3f71d1defd3fa999dfa36953755c690689799962b48bebd836974e8cf90120000000000000000000000000000000000000000000000000000000000000000000000000";
+
+    /// `OP_RETURN OP_13` followed by the LEB128 encoding of `integers`
+    fn runestone_script(integers: &[u128]) -> bitcoin::ScriptBuf {
+        let mut payload = Vec::new();
+        for &n in integers {
+            let mut n = n;
+            while n >= 0x80 {
+                payload.push(n as u8 | 0x80);
+                n >>= 7;
+            }
+            payload.push(n as u8);
+        }
+        let mut script = vec![0x6a, 0x5d, payload.len() as u8];
+        script.extend(payload);
+        bitcoin::ScriptBuf::from_bytes(script)
+    }
+
+    #[test]
+    fn test_has_witness() {
+        let legacy = decode_transaction(LEGACY_TX_HEX).unwrap();
+        assert!(!has_witness(&legacy));
+        assert!(!is_fully_segwit(&legacy));
+
+        let mut segwit = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(has_witness(&segwit));
+        assert!(is_fully_segwit(&segwit));
+
+        // One legacy input makes it mixed
+        segwit.input.push(legacy.input[0].clone());
+        assert!(has_witness(&segwit));
+        assert!(!is_fully_segwit(&segwit));
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert!(is_fully_segwit(&coinbase));
+    }
+
+    #[test]
+    fn test_strip_witness() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let stripped = strip_witness(&tx);
+        assert_eq!(stripped.len(), tx.base_size());
+        assert_eq!(
+            bitcoin::Txid::from_byte_array(
+                bitcoin::hashes::sha256d::Hash::hash(&stripped).to_byte_array()
+            ),
+            tx.compute_txid()
+        );
+
+        // Legacy transactions are unchanged
+        let legacy = hex::decode(LEGACY_TX_HEX).unwrap();
+        assert_eq!(
+            strip_witness(&decode_transaction_bytes(&legacy).unwrap()),
+            legacy
+        );
+    }
+
+    #[test]
+    fn test_input_serialized_size() {
+        // 36-byte outpoint, empty scriptSig with its length byte, 4-byte sequence
+        let segwit = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(input_serialized_size(&segwit.input[0]), (41, 108));
+
+        // Every element adds up to the stripped size, plus version, counts and lock time
+        let legacy = decode_transaction(LEGACY_TX_HEX).unwrap();
+        let elements: usize = legacy
+            .input
+            .iter()
+            .map(|input| input_serialized_size(input).0)
+            .chain(
+                legacy
+                    .output
+                    .iter()
+                    .map(|output| encode::serialize(output).len()),
+            )
+            .sum();
+        assert_eq!(input_serialized_size(&legacy.input[0]).1, 0);
+        assert_eq!(elements + 4 + 1 + 1 + 4, legacy.base_size());
+    }
+
+    #[test]
+    fn test_type_summary() {
+        let opts = RenderOptions::default();
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(
+            type_summary(&tx, &opts),
+            "1×P2WPKH → 1×P2SH, 1×P2TR, 1×P2WPKH"
+        );
+
+        let mut tx = tx;
+        tx.output[0].script_pubkey = tx.output[2].script_pubkey.clone();
+        tx.input.push(tx.input[0].clone());
+        assert_eq!(type_summary(&tx, &opts), "2×P2WPKH → 2×P2WPKH, 1×P2TR");
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert_eq!(
+            type_summary(&coinbase, &opts),
+            "1×Coinbase → 1×P2WPKH, 1×OP_RETURN"
+        );
+    }
+
+    #[test]
+    fn test_decode_runestone() {
+        // Mint 840000:1, then send 1000 of it to output 1
+        let script = runestone_script(&[20, 840_000, 20, 1, 0, 840_000, 1, 1000, 1]);
+        let runestone = decode_runestone(&script).unwrap();
+        assert_eq!(runestone.cenotaph, None);
+        let id = RuneId {
+            block: 840_000,
+            tx: 1,
+        };
+        assert_eq!(runestone.mint, Some(id));
+        assert_eq!(
+            runestone.edicts,
+            vec![Edict {
+                id,
+                amount: 1000,
+                output: 1
+            }]
+        );
+
+        // Etching "A•A" (26) with symbol "$", premine and open mint terms
+        let script = runestone_script(&[2, 3, 4, 26, 3, 1, 5, 36, 6, 21, 10, 100, 8, 5]);
+        let etching = decode_runestone(&script).unwrap().etching.unwrap();
+        assert_eq!(etching.rune.as_deref(), Some("A•A"));
+        assert_eq!(etching.symbol, Some('$'));
+        assert_eq!(etching.premine, Some(21));
+        let terms = etching.terms.unwrap();
+        assert_eq!((terms.cap, terms.amount), (Some(5), Some(100)));
+
+        // Not a runestone without OP_13
+        let plain = bitcoin::ScriptBuf::from_hex("6a0100").unwrap();
+        assert_eq!(decode_runestone(&plain), None);
+    }
+
+    #[test]
+    fn test_decode_runestone_cenotaphs() {
+        let reason = |script: &bitcoin::Script| decode_runestone(script).unwrap().cenotaph;
+
+        assert_eq!(
+            reason(&runestone_script(&[24, 1])),
+            Some("unrecognized even tag")
+        );
+        assert_eq!(
+            reason(&runestone_script(&[2, 8])),
+            Some("unrecognized flag")
+        );
+        assert_eq!(reason(&runestone_script(&[22])), Some("truncated field"));
+        assert_eq!(
+            reason(&runestone_script(&[0, 1, 0, 5])),
+            Some("trailing integers after the edicts")
+        );
+        // Unknown odd tags are ignored
+        assert_eq!(reason(&runestone_script(&[25, 1])), None);
+        // An unterminated varint and a non-push opcode
+        let unterminated = bitcoin::ScriptBuf::from_hex("6a5d0180").unwrap();
+        assert_eq!(reason(&unterminated), Some("malformed LEB128 integer"));
+        let opcode = bitcoin::ScriptBuf::from_hex("6a5d51").unwrap();
+        assert_eq!(reason(&opcode), Some("opcode in the payload"));
+
+        // An edict to an output the transaction doesn't have
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.output[0].script_pubkey = runestone_script(&[0, 840_000, 1, 1000, 7]);
+        let (idx, runestone) = tx_runestone(&tx).unwrap();
+        assert_eq!(idx, 0);
+        assert_eq!(runestone.cenotaph, Some("edict to a missing output"));
+    }
+
+    #[test]
+    fn test_read_leb128_and_rune_name() {
+        assert_eq!(read_leb128(&[0xac, 0x02]), Some((300, 2)));
+        assert_eq!(read_leb128(&[0x80]), None);
+        assert_eq!(read_leb128(&[0xff; 19]), None);
+        let mut max = vec![0xff; 18];
+        max.push(0x03);
+        assert_eq!(read_leb128(&max), Some((u128::MAX, 19)));
+
+        assert_eq!(rune_name(0, 0), "A");
+        assert_eq!(rune_name(25, 0), "Z");
+        assert_eq!(rune_name(26, 0), "AA");
+        assert_eq!(rune_name(u128::MAX, 0), "BCGDENLQRQWDSLRUGSNLBTMFIJAV");
+        assert_eq!(rune_name(26 + 26 * 26, 0b11), "A•A•A");
+    }
+
+    #[test]
+    fn test_value_histogram() {
+        // 120,751, 8,910 and 555,247 sats
+        let histogram = value_histogram(&decode_transaction(SEGWIT_TX_HEX).unwrap());
+        assert_eq!(histogram.len(), 7);
+        assert_eq!(histogram[1], ("< 0.001 BTC", 1));
+        assert_eq!(histogram[2], ("< 0.01 BTC", 2));
+        assert_eq!(histogram.iter().map(|&(_, count)| count).sum::<usize>(), 3);
+
+        // 50 BTC subsidy and a zero-value witness commitment
+        let histogram = value_histogram(&decode_transaction(COINBASE_TX_HEX).unwrap());
+        assert_eq!(histogram[0], ("0 BTC", 1));
+        assert_eq!(histogram[6], ("10+ BTC", 1));
+    }
+
+    #[test]
+    fn test_sequence_breakdown() {
+        use bitcoin::Sequence;
+
+        assert_eq!(
+            sequence_breakdown(Sequence(0x0040_000a)),
+            "disable=false, type=time, value=10 (5120s)"
+        );
+        assert_eq!(
+            sequence_breakdown(Sequence(144)),
+            "disable=false, type=blocks, value=144 (144 blocks)"
+        );
+        assert_eq!(
+            sequence_breakdown(Sequence::MAX),
+            "disable=true, type=time, value=65535 (no relative timelock), reserved bits 0x7fbf0000 (ignored)"
+        );
+        assert_eq!(
+            sequence_breakdown(Sequence(0x8000_0000)),
+            "disable=true, type=blocks, value=0 (no relative timelock)"
+        );
+        // Bits outside the BIP68 fields don't change the meaning
+        assert_eq!(
+            sequence_breakdown(Sequence(0x0001_0001)),
+            "disable=false, type=blocks, value=1 (1 block), reserved bits 0x00010000 (ignored)"
+        );
+    }
+
+    #[test]
+    fn test_classify_output_with_registered_pattern() {
+        // Outputs of a made-up protocol: OP_RETURN followed by a push starting with "XYZ"
+        fn is_xyz(output: &bitcoin::TxOut) -> bool {
+            output
+                .script_pubkey
+                .as_bytes()
+                .starts_with(&[0x6a, 0x05, b'X', b'Y', b'Z'])
+        }
+        let tagged = bitcoin::TxOut {
+            value: bitcoin::Amount::ZERO,
+            script_pubkey: bitcoin::ScriptBuf::from_hex("6a0558595a0102").unwrap(),
+        };
+        let other = bitcoin::TxOut {
+            value: bitcoin::Amount::ZERO,
+            script_pubkey: bitcoin::ScriptBuf::from_hex("6a0301020304").unwrap(),
+        };
+
+        let builtin = RenderOptions::default();
+        assert_eq!(classify_output(&tagged, &builtin), "nulldata");
+
+        let opts = RenderOptions::default().register_pattern("xyz", is_xyz);
+        assert_eq!(classify_output(&tagged, &opts), "xyz");
+        assert_eq!(classify_output(&other, &opts), "nulldata");
+
+        let tx = decode_transaction(LEGACY_TX_HEX).unwrap();
+        assert_eq!(classify_output(&tx.output[0], &opts), "pubkeyhash");
+    }
+
+    #[test]
+    fn test_spendability() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let output = |hex: &str| bitcoin::TxOut {
+            value: bitcoin::Amount::ZERO,
+            script_pubkey: bitcoin::ScriptBuf::from_hex(hex).unwrap(),
+        };
+        assert_eq!(spendability(&tx.output[2]), "Spendable via signature");
+        assert!(spendability(&tx.output[0]).contains("committed script"));
+        assert!(spendability(&tx.output[1]).contains("key path"));
+        assert_eq!(
+            spendability(&output("51024e73")),
+            "Anyone-can-spend, no signature needed (P2A)"
+        );
+        assert_eq!(
+            spendability(&output("6a0401020304")),
+            "Provably unspendable (OP_RETURN)"
+        );
+        assert!(
+            spendability(&output("52020000")).starts_with("Anyone-can-spend under current rules")
+        );
+        assert!(spendability(&output("0003aabbcc")).starts_with("Unspendable"));
+        assert_eq!(
+            spendability(&output("")),
+            "Anyone-can-spend, no signature needed"
+        );
+        assert!(spendability(&output("ac")).starts_with("Non-standard"));
+
+        let opts = RenderOptions::default().color(false).verbose(true);
+        assert!(render_transaction(&tx, &opts).contains("Spendability"));
+        assert!(!render_transaction(&tx, &opts.verbose(false)).contains("Spendability"));
+    }
+
+    #[test]
+    fn test_decode_transaction_bech32() {
+        use bitcoin::bech32::{Bech32, Bech32m, Hrp};
+
+        let expected = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let bytes = hex::decode(SEGWIT_TX_HEX).unwrap();
+        let hrp = Hrp::parse("tx").unwrap();
+        let encoded = bitcoin::bech32::encode::<Bech32m>(hrp, &bytes).unwrap();
+        assert_eq!(decode_transaction_bech32(&encoded), Ok(expected.clone()));
+        let encoded = bitcoin::bech32::encode::<Bech32>(hrp, &bytes).unwrap();
+        assert_eq!(decode_transaction_bech32(&encoded), Ok(expected));
+        assert_eq!(decode_bech32_payload(&encoded), Ok(bytes));
+
+        // Change the last checksum character
+        let mut corrupted = encoded.clone();
+        let last = corrupted.pop().unwrap();
+        corrupted.push(if last == 'q' { 'p' } else { 'q' });
+        assert!(
+            decode_transaction_bech32(&corrupted)
+                .unwrap_err()
+                .starts_with("Invalid bech32 string")
+        );
+
+        let address = "bc1q3kejffwyh7pqw9cfzzrhd80wxq5qnn9jvem8v9";
+        assert!(
+            decode_transaction_bech32(address)
+                .unwrap_err()
+                .contains("prefix of Bitcoin addresses")
+        );
+    }
+
+    #[test]
+    fn test_classify_witness_item() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let witness = &tx.input[0].witness;
+        assert_eq!(classify_witness_item(&[]), WitnessItemKind::Empty);
+        assert_eq!(
+            classify_witness_item(witness.nth(0).unwrap()),
+            WitnessItemKind::EcdsaSignature { non_strict: None }
+        );
+        assert_eq!(
+            classify_witness_item(witness.nth(1).unwrap()),
+            WitnessItemKind::CompressedPubkey
+        );
+        assert_eq!(
+            classify_witness_item(&[0x04; 65]),
+            WitnessItemKind::UncompressedPubkey
+        );
+        assert_eq!(
+            classify_witness_item(&[0x11; 64]),
+            WitnessItemKind::SchnorrSignature
+        );
+        assert_eq!(
+            classify_witness_item(&[0x11; 65]),
+            WitnessItemKind::SchnorrSignature
+        );
+        // A Schnorr signature that happens to start with the DER sequence tag
+        let mut schnorr = [0x11; 64];
+        schnorr[0] = 0x30;
+        assert_eq!(
+            classify_witness_item(&schnorr),
+            WitnessItemKind::SchnorrSignature
+        );
+        // While a 64-byte DER signature (28-byte R, 29-byte S) is still ECDSA
+        let mut der = vec![0x30, 61, 0x02, 28];
+        der.extend([0x11; 28]);
+        der.extend([0x02, 29]);
+        der.extend([0x11; 29]);
+        der.push(0x01);
+        assert_eq!(
+            classify_witness_item(&der),
+            WitnessItemKind::EcdsaSignature { non_strict: None }
+        );
+
+        let mut control_block = vec![0xc1];
+        control_block.extend_from_slice(&[0x79; 32 * 3]);
+        assert_eq!(
+            classify_witness_item(&control_block[..33]),
+            WitnessItemKind::ControlBlock
+        );
+        assert_eq!(
+            classify_witness_item(&control_block),
+            WitnessItemKind::ControlBlock
+        );
+
+        assert_eq!(classify_witness_item(&[0x51; 101]), WitnessItemKind::Script);
+        assert_eq!(
+            classify_witness_item(&[0x51; 100]),
+            WitnessItemKind::Data(100)
+        );
+        assert_eq!(classify_witness_item(&[0x01]), WitnessItemKind::Data(1));
+        // Too short to be a signature despite the DER sequence tag
+        assert_eq!(classify_witness_item(&[0x30; 8]), WitnessItemKind::Data(8));
+    }
+
+    #[test]
+    fn test_witness_program() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // v0 with a 20-byte program (P2WPKH)
+        assert_eq!(
+            witness_program(&tx.output[2].script_pubkey),
+            Some((
+                0,
+                hex::decode("8db324a5c4bf820717091087769dee302809ccb2").unwrap()
+            ))
+        );
+        // v1 with a 32-byte program (P2TR)
+        let (version, program) = witness_program(&tx.output[1].script_pubkey).unwrap();
+        assert_eq!((version, program.len()), (1, 32));
+        // P2SH is not a witness program
+        assert_eq!(witness_program(&tx.output[0].script_pubkey), None);
+
+        // v0 with a 32-byte program (P2WSH)
+        let p2wsh = bitcoin::ScriptBuf::new_p2wsh(&bitcoin::WScriptHash::all_zeros());
+        assert_eq!(witness_program(&p2wsh), Some((0, vec![0; 32])));
+        // v0 programs must be 20 or 32 bytes
+        let bad_v0 = bitcoin::ScriptBuf::from_hex("0003aabbcc").unwrap();
+        assert_eq!(witness_program(&bad_v0), None);
+
+        let anchor = bitcoin::ScriptBuf::from_hex("51024e73").unwrap();
+        assert_eq!(witness_program(&anchor), Some((1, vec![0x4e, 0x73])));
+    }
+
+    #[test]
+    fn test_decode_odd_length_hex() {
+        let err = decode_transaction(&SEGWIT_TX_HEX[1..]).unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "Invalid hex string: transaction hex has an odd number of characters ({}); did you truncate it or include a stray character?",
+                SEGWIT_TX_HEX.len() - 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_decode_0x_prefixed_hex() {
+        let expected = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        for prefix in ["0x", "0X", " 0x"] {
+            let tx = decode_transaction(&format!("{}{}\n", prefix, SEGWIT_TX_HEX)).unwrap();
+            assert_eq!(tx, expected);
+        }
+        assert_eq!(strip_hex("0x"), "");
+        assert_eq!(strip_hex("00x1"), "00x1");
+    }
+
+    #[test]
+    fn test_compact_size_len() {
+        assert_eq!(compact_size_len(0), 1);
+        assert_eq!(compact_size_len(1), 1);
+        assert_eq!(compact_size_len(252), 1);
+        assert_eq!(compact_size_len(253), 3);
+        assert_eq!(compact_size_len(0xffff), 3);
+        assert_eq!(compact_size_len(0x10000), 5);
+        assert_eq!(compact_size_len(0xffff_ffff), 5);
+        assert_eq!(compact_size_len(0x1_0000_0000), 9);
+    }
+
+    #[test]
+    fn test_input_vsize_segwit() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // P2WPKH input: 41 bytes base (36 + 1 + 0 + 4), witness ~107 bytes
+        // Weight = 41*4 + 107 = 271, vsize = ceil(271/4) = 68
+        assert_eq!(input_vsize(&tx.input[0]), 68);
+    }
+
+    #[test]
+    fn test_input_vsize_coinbase() {
+        let tx = decode_transaction(COINBASE_TX_HEX).unwrap();
+        // Coinbase input: base = 36 + 1 + 5 + 4 = 46, witness = 1 + 1 + 32 = 34
+        // Weight = 46*4 + 34 = 218, vsize = ceil(218/4) = 55
+        assert_eq!(input_vsize(&tx.input[0]), 55);
+    }
+
+    #[test]
+    fn test_witness_size() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // count(1) + signature(1 + 72) + pubkey(1 + 33) = 108
+        assert_eq!(witness_size(&tx.input[0]), 108);
+        assert_eq!(tx.input[0].witness.size(), 108);
+    }
+
+    #[test]
+    fn test_output_vsize_p2sh() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // P2SH output: 8 + 1 + 23 = 32
+        assert_eq!(output_vsize(&tx.output[0]), 32);
+    }
+
+    #[test]
+    fn test_output_vsize_p2tr() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // P2TR output: 8 + 1 + 34 = 43
+        assert_eq!(output_vsize(&tx.output[1]), 43);
+    }
+
+    #[test]
+    fn test_output_vsize_p2wpkh() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        // P2WPKH output: 8 + 1 + 22 = 31
+        assert_eq!(output_vsize(&tx.output[2]), 31);
+    }
+
+    #[test]
+    fn test_truc_version() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(!is_truc(&tx));
+
+        tx.version = bitcoin::transaction::Version(3);
+        assert!(is_truc(&tx));
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("3 (TRUC)"));
+        assert!(!rendered.contains("TRUC Warning"));
+    }
+
+    #[test]
+    fn test_is_replaceable() {
+        // SegWit sample signals RBF with sequence 0xfdffffff
+        assert!(is_replaceable(&decode_transaction(SEGWIT_TX_HEX).unwrap()));
+        // Coinbase input uses a final sequence
+        assert!(!is_replaceable(
+            &decode_transaction(COINBASE_TX_HEX).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_replaceability() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(replaceability(&tx, false), "Replaceable: opt-in (BIP125)");
+        assert_eq!(replaceability(&tx, true), "Replaceable: opt-in (BIP125)");
+
+        tx.input[0].sequence = bitcoin::Sequence::ENABLE_LOCKTIME_NO_RBF;
+        assert!(replaceability(&tx, false).starts_with("Replaceable: only under full-RBF policy"));
+        assert!(replaceability(&tx, true).contains("full-RBF nodes replace it anyway"));
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert!(replaceability(&coinbase, true).starts_with("Not replaceable"));
+    }
+
+    #[test]
+    fn test_lightning_script_hint() {
+        use bitcoin::opcodes::all::*;
+        use bitcoin::script::Builder;
+
+        let pubkey = [0x02; 33];
+        let to_local = Builder::new()
+            .push_opcode(OP_IF)
+            .push_slice(pubkey)
+            .push_opcode(OP_ELSE)
+            .push_int(144)
+            .push_opcode(OP_CSV)
+            .push_opcode(OP_DROP)
+            .push_slice(pubkey)
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(
+            lightning_script_hint(&to_local),
+            Some(LightningHint::ToLocal)
+        );
+
+        let anchor = Builder::new()
+            .push_slice(pubkey)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_IFDUP)
+            .push_opcode(OP_NOTIF)
+            .push_int(16)
+            .push_opcode(OP_CSV)
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(lightning_script_hint(&anchor), Some(LightningHint::Anchor));
+
+        let htlc_prefix = Builder::new()
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice([0x11; 20])
+            .push_opcode(OP_EQUAL)
+            .push_opcode(OP_IF)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_ELSE)
+            .push_slice(pubkey)
+            .push_opcode(OP_SWAP)
+            .push_opcode(OP_SIZE)
+            .push_int(32)
+            .push_opcode(OP_EQUAL);
+        let offered = htlc_prefix.clone().push_opcode(OP_NOTIF).into_script();
+        assert_eq!(
+            lightning_script_hint(&offered),
+            Some(LightningHint::OfferedHtlc)
+        );
+        let received = htlc_prefix.push_opcode(OP_IF).into_script();
+        assert_eq!(
+            lightning_script_hint(&received),
+            Some(LightningHint::ReceivedHtlc)
+        );
+
+        // A plain P2WPKH output script is not a Lightning template
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(lightning_script_hint(&tx.output[2].script_pubkey), None);
+    }
+
+    #[test]
+    fn test_burn_reason() {
+        use bitcoin::{Amount, ScriptBuf, TxOut};
+
+        let op_return = ScriptBuf::new_op_return([0xde, 0xad]);
+        let burn = TxOut {
+            value: Amount::from_sat(1_000),
+            script_pubkey: op_return.clone(),
+        };
+        assert_eq!(burn_reason(&burn), Some("OP_RETURN with value"));
+
+        // Zero-value data carriers are the normal OP_RETURN use
+        let data = TxOut {
+            value: Amount::ZERO,
+            script_pubkey: op_return,
+        };
+        assert_eq!(burn_reason(&data), None);
+
+        let eater = "1BitcoinEaterAddressDontSendf59kuE"
+            .parse::<bitcoin::Address<_>>()
+            .unwrap()
+            .assume_checked();
+        let eaten = TxOut {
+            value: Amount::from_sat(1_000),
+            script_pubkey: eater.script_pubkey(),
+        };
+        assert_eq!(burn_reason(&eaten), Some("known burn address"));
+
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(tx.output.iter().all(|o| burn_reason(o).is_none()));
+    }
+
+    #[test]
+    fn test_p2pk_output() {
+        // Coinbase of block 1, paying to an uncompressed public key
+        let tx = decode_transaction("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82cbf2342c858eeac00000000").unwrap();
+        assert_eq!(
+            tx.compute_txid().to_string(),
+            "0e3e2357e806b6cdb1f70b54c3a3a17b6714ee1f0e68bebb44a74b1efd512098"
+        );
+        assert!(output_address(&tx.output[0], Network::Bitcoin).is_none());
+        assert_eq!(
+            p2pk_address(&tx.output[0], Network::Bitcoin)
+                .unwrap()
+                .to_string(),
+            "12c6DSiU4Rq3P4ZxziKxzrL5LmMBrzjrJX"
+        );
+
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("P2PK (Pay-to-Public-Key)"));
+        assert!(
+            rendered.contains("0496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52")
+        );
+        assert!(explain_transaction(&tx, &opts).contains("bare public key (P2PK)"));
+    }
+
+    #[test]
+    fn test_render_transaction_without_color() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let rendered = render_transaction(&tx, &opts);
+
+        for title in ["TRANSACTION OVERVIEW", "INPUTS", "OUTPUTS", "SUMMARY"] {
+            assert!(rendered.contains(title), "missing section {}", title);
+        }
+        assert!(rendered.contains(&tx.compute_txid().to_string()));
+        assert!(!rendered.contains('\x1b'));
+        // Verbose-only sections stay hidden by default
+        assert!(!rendered.contains("REPLACEABILITY"));
+    }
+
+    #[test]
+    fn test_electrum_scripthash() {
+        // Genesis coinbase address 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa
+        let output = bitcoin::TxOut {
+            value: bitcoin::Amount::ZERO,
+            script_pubkey: bitcoin::ScriptBuf::from_hex(
+                "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+            )
+            .unwrap(),
+        };
+        assert_eq!(
+            electrum_scripthash(&output),
+            "8b01df4e368ea28f8dc0423bcf7a4923e3a12d307c875e47a0cfbf90b5c39161"
+        );
+    }
+
+    #[test]
+    fn test_nonstandard_script_sig() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let mut input = tx.input[0].clone();
+        assert_eq!(nonstandard_script_sig(&input), None);
+
+        input.script_sig = bitcoin::ScriptBuf::from_hex("0051").unwrap();
+        assert_eq!(nonstandard_script_sig(&input), None);
+
+        // OP_DUP is not a push
+        input.script_sig = bitcoin::ScriptBuf::from_hex("0076").unwrap();
+        assert_eq!(
+            nonstandard_script_sig(&input),
+            Some("contains non-push opcodes")
+        );
+
+        input.script_sig = bitcoin::ScriptBuf::from_bytes(vec![0x51; 1651]);
+        assert_eq!(nonstandard_script_sig(&input), Some("exceeds 1650 bytes"));
+
+        // Coinbase scriptSigs are arbitrary data
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert_eq!(nonstandard_script_sig(&coinbase.input[0]), None);
+    }
+
+    #[test]
+    fn test_bip69() {
+        // Outputs are 120751, 8910 and 555247 sats
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(!is_bip69_sorted(&tx));
+        let violations = bip69_violations(&tx);
+        assert!(violations.inputs.is_empty());
+        assert_eq!(violations.outputs, vec![0]);
+
+        tx.output.sort_by(|a, b| {
+            (a.value, a.script_pubkey.as_bytes()).cmp(&(b.value, b.script_pubkey.as_bytes()))
+        });
+        assert!(is_bip69_sorted(&tx));
+
+        // Equal txids fall back to the output index
+        let mut input = tx.input[0].clone();
+        input.previous_output.vout = 1;
+        tx.input.push(input);
+        assert_eq!(bip69_violations(&tx).inputs, vec![0]);
+    }
+
+    #[test]
+    fn test_detect_nested_segwit() {
+        let tx = decode_transaction(NESTED_SEGWIT_TX_HEX).unwrap();
+        assert_eq!(detect_input_type(&tx.input[0]), InputType::NestedP2wpkh);
+
+        let mut input = tx.input[0].clone();
+        let mut redeem_script = vec![0x22, 0x00, 0x20];
+        redeem_script.extend([0u8; 32]);
+        input.script_sig = bitcoin::ScriptBuf::from_bytes(redeem_script);
+        assert_eq!(detect_input_type(&input), InputType::NestedP2wsh);
+
+        // Native SegWit inputs keep an empty scriptSig
+        let segwit = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(detect_input_type(&segwit.input[0]), InputType::P2wpkh);
+        assert_eq!(
+            InputType::P2wpkh.to_string(),
+            "P2WPKH (Pay-to-Witness-Public-Key-Hash)"
+        );
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert_eq!(detect_input_type(&coinbase.input[0]), InputType::Coinbase);
+    }
+
+    #[test]
+    fn test_detect_legacy_input_types() {
+        use bitcoin::script::{Builder, Instruction, PushBytesBuf};
+
+        // <sig> <pubkey>
+        let tx = decode_transaction(LEGACY_TX_HEX).unwrap();
+        assert_eq!(detect_input_type(&tx.input[0]), InputType::P2pkh);
+
+        // <sig> alone spends a P2PK output
+        let mut input = tx.input[0].clone();
+        let Some(Ok(Instruction::PushBytes(sig))) = tx.input[0].script_sig.instructions().next()
+        else {
+            panic!("legacy scriptSig starts with a signature");
+        };
+        input.script_sig = Builder::new().push_slice(sig).into_script();
+        assert_eq!(detect_input_type(&input), InputType::P2pk);
+
+        // OP_0 <sig> <sig> <redeemScript>, whatever its length
+        let signature = PushBytesBuf::try_from(sig.as_bytes().to_vec()).unwrap();
+        let redeem_script = PushBytesBuf::try_from(vec![0x52; 71]).unwrap();
+        input.script_sig = Builder::new()
+            .push_int(0)
+            .push_slice(&signature)
+            .push_slice(&signature)
+            .push_slice(redeem_script)
+            .into_script();
+        assert_eq!(detect_input_type(&input), InputType::P2sh);
+
+        // A signature and a pubkey-sized push that isn't a key is a P2SH spend
+        input.script_sig = Builder::new()
+            .push_slice(&signature)
+            .push_slice([0x05; 33])
+            .into_script();
+        assert_eq!(detect_input_type(&input), InputType::P2sh);
+    }
+
+    #[test]
+    fn test_core_decode() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let json = serde_json::to_value(core_decode(&tx, Network::Bitcoin)).unwrap();
+        assert_eq!(
+            json["txid"],
+            "7562fb02144caac71f8518b10ef8af988df411519a3a860e71a95b58c44850a0"
+        );
+        assert_eq!(json["vsize"], 185);
+        assert_eq!(json["vin"][0]["vout"], 2);
+        assert_eq!(json["vin"][0]["scriptSig"]["hex"], "");
+        assert_eq!(json["vin"][0]["txinwitness"].as_array().unwrap().len(), 2);
+        assert_eq!(json["vout"][0]["value"], 0.00120751);
+        assert_eq!(json["vout"][0]["scriptPubKey"]["type"], "scripthash");
+        assert_eq!(
+            json["vout"][1]["scriptPubKey"]["type"],
+            "witness_v1_taproot"
+        );
+        assert_eq!(
+            json["vout"][2]["scriptPubKey"]["type"],
+            "witness_v0_keyhash"
+        );
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        let json = serde_json::to_value(core_decode(&coinbase, Network::Bitcoin)).unwrap();
+        assert_eq!(json["vin"][0]["coinbase"], "02e8030101");
+        assert!(json["vin"][0].get("txid").is_none());
+        assert_eq!(json["vout"][1]["scriptPubKey"]["type"], "nulldata");
+        assert!(json["vout"][1]["scriptPubKey"].get("address").is_none());
+    }
+
+    #[test]
+    fn test_lock_time_active() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.lock_time = bitcoin::absolute::LockTime::from_height(800_000).unwrap();
+        assert!(is_lock_time_active(&tx));
+        let explained = explain_transaction(&tx, &RenderOptions::default());
+        assert!(explained.contains("lock time of block height 800000 is enforced"));
+
+        tx.input[0].sequence = bitcoin::Sequence::MAX;
+        assert!(!is_lock_time_active(&tx));
+        let rendered = render_transaction(
+            &tx,
+            &RenderOptions {
+                color: false,
+                ..RenderOptions::default()
+            },
+        );
+        assert!(rendered.contains("No (all input sequences are final)"));
+    }
+
+    #[test]
+    fn test_parse_inscription() {
+        use bitcoin::opcodes::OP_FALSE;
+        use bitcoin::opcodes::all::{OP_CHECKSIG, OP_ENDIF, OP_IF, OP_PUSHNUM_1};
+        use bitcoin::script::Builder;
+
+        let script = Builder::new()
+            .push_slice([0x11; 32])
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF)
+            .push_slice(b"ord")
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(b"text/plain;charset=utf-8")
+            .push_opcode(OP_FALSE)
+            .push_slice(b"Hello, ")
+            .push_slice(b"world!")
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(
+            parse_inscription(&script),
+            Some(Inscription {
+                content_type: Some("text/plain;charset=utf-8".to_string()),
+                content: b"Hello, world!".to_vec(),
+            })
+        );
+
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let mut control_block = vec![0xc0];
+        control_block.extend([0x22; 32]);
+        tx.input[0].witness =
+            bitcoin::Witness::from_slice(&[vec![0x33; 64], script.to_bytes(), control_block]);
+        let rendered = render_transaction(
+            &tx,
+            &RenderOptions {
+                color: false,
+                ..RenderOptions::default()
+            },
+        );
+        assert!(rendered.contains("INSCRIPTIONS (1)"));
+        assert!(rendered.contains("Hello, world!"));
+
+        let plain = Builder::new()
+            .push_slice([0x11; 32])
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(parse_inscription(&plain), None);
+    }
+
+    #[test]
+    fn test_markdown_table_style() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let rendered = render_transaction(
+            &tx,
+            &RenderOptions {
+                color: false,
+                table_style: TableStyle::Markdown,
+                ..RenderOptions::default()
+            },
+        );
+        assert!(rendered.contains("| Field "));
+        assert!(rendered.contains("|---"));
+        // Multi-line witness cells are kept on a single row
+        assert!(rendered.contains("<br>    Type: "));
+        assert_eq!("BOX".parse(), Ok(TableStyle::Box));
+    }
+
+    #[test]
+    fn test_decode_transaction_bytes() {
+        let bytes = hex::decode(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(
+            decode_transaction_bytes(&bytes),
+            decode_transaction(SEGWIT_TX_HEX)
+        );
+        assert!(decode_transaction_bytes(&bytes[..100]).is_err());
+    }
+
+    #[test]
+    fn test_serialization_format() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(has_segwit_marker(&hex::decode(SEGWIT_TX_HEX).unwrap()));
+        assert_eq!(serialization_format(&tx), "SegWit (marker 0x00 0x01)");
+
+        tx.input[0].witness.clear();
+        let legacy = encode::serialize(&tx);
+        assert!(!has_segwit_marker(&legacy));
+        assert_eq!(serialization_format(&tx), "Legacy");
+
+        // Marker and flag with one empty witness stack per input
+        let split = legacy.len() - 4;
+        let mut empty_witness = legacy[..4].to_vec();
+        empty_witness.extend([0x00, 0x01]);
+        empty_witness.extend(&legacy[4..split]);
+        empty_witness.push(0x00);
+        empty_witness.extend(&legacy[split..]);
+        let err = decode_transaction_bytes(&empty_witness).unwrap_err();
+        assert!(err.contains("SegWit-serialized without any witness data"));
+    }
+
+    #[test]
+    fn test_psbt_input_amounts() {
+        use base64::{Engine, engine::general_purpose::STANDARD};
+
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.input[0].witness.clear();
+        let funding = decode_transaction(NESTED_SEGWIT_TX_HEX).unwrap();
+        tx.input.push(bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(funding.compute_txid(), 0),
+            ..tx.input[0].clone()
+        });
+
+        let mut psbt = Psbt::from_unsigned_tx(tx.clone()).unwrap();
+        assert_eq!(psbt_input_amounts(&psbt), [None, None]);
+
+        psbt.inputs[0].witness_utxo = Some(bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(700_000),
+            script_pubkey: bitcoin::ScriptBuf::new(),
+        });
+        assert_eq!(psbt_input_amounts(&psbt), [Some(700_000), None]);
+        psbt.inputs[1].non_witness_utxo = Some(funding.clone());
+        let amounts = [Some(700_000), Some(funding.output[0].value.to_sat())];
+        assert_eq!(psbt_input_amounts(&psbt), amounts);
+
+        let bytes = psbt.serialize();
+        assert_eq!(
+            psbt_input_amounts(&decode_psbt(&STANDARD.encode(&bytes)).unwrap()),
+            amounts
+        );
+        assert_eq!(
+            psbt_input_amounts(&decode_psbt(&hex::encode(&bytes)).unwrap()),
+            amounts
+        );
+        assert!(decode_psbt("not a psbt").is_err());
+
+        let opts = RenderOptions::default()
+            .color(false)
+            .input_amounts(vec![Some(700_000), None]);
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("0.00700000 BTC (700,000 satoshis)"));
+        assert!(rendered.contains("input amount unknown"));
+    }
+
+    #[test]
+    fn test_follow_spend() {
+        let prev = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let mut tx = decode_transaction(NESTED_SEGWIT_TX_HEX).unwrap();
+        assert!(
+            follow_spend(&tx, &prev)
+                .unwrap_err()
+                .contains("not spent by any input")
+        );
+
+        tx.input[0].previous_output = bitcoin::OutPoint::new(prev.compute_txid(), 2);
+        let spent = follow_spend(&tx, &prev).unwrap();
+        assert_eq!(spent, [Some(prev.output[2].clone())]);
+        // The P2WPKH prevout rules out the nested P2SH guess from the scriptSig alone
+        assert_eq!(detect_input_type(&tx.input[0]), InputType::NestedP2wpkh);
+        assert_eq!(
+            detect_input_type_with_prevout(&tx.input[0], &prev.output[2]),
+            InputType::P2wpkh
+        );
+
+        let rendered = render_transaction(
+            &tx,
+            &RenderOptions::default().color(false).spent_outputs(spent),
+        );
+        assert!(rendered.contains("bc1q3kejffwyh7pqw9cfzzrhd80wxq5qnn9jvem8v9 (P2WPKH)"));
+
+        tx.input[0].previous_output.vout = 3;
+        assert!(
+            follow_spend(&tx, &prev)
+                .unwrap_err()
+                .contains("has 3 outputs")
+        );
+    }
+
+    #[test]
+    fn test_decode_transaction_list() {
+        let segwit = hex::decode(SEGWIT_TX_HEX).unwrap();
+        let coinbase = hex::decode(COINBASE_TX_HEX).unwrap();
+        let blob = [&[0x02][..], &segwit, &coinbase].concat();
+
+        let list = decode_transaction_list(&blob).unwrap();
+        assert_eq!(list.declared_count, 2);
+        assert_eq!(list.transactions.len(), 2);
+        assert_eq!(list.warning(), None);
+
+        // Declared count disagrees with the contents
+        let mut overstated = blob.clone();
+        overstated[0] = 0x03;
+        let list = decode_transaction_list(&overstated).unwrap();
+        assert_eq!(
+            list.warning().unwrap(),
+            "prefix declares 3 transactions but the blob holds 2"
+        );
+
+        // Trailing garbage stops decoding
+        let list = decode_transaction_list(&[&blob[..], &[0x01, 0x02]].concat()).unwrap();
+        assert_eq!(list.transactions.len(), 2);
+        assert!(list.error.is_some());
+    }
+
+    #[test]
+    fn test_decode_transaction_limited() {
+        assert!(decode_transaction_limited(SEGWIT_TX_HEX, 267).is_ok());
+        assert_eq!(
+            decode_transaction_limited(SEGWIT_TX_HEX, 266).unwrap_err(),
+            "Transaction is 267 bytes, exceeding the 266 byte limit"
+        );
+    }
+
+    #[test]
+    fn test_p2sh_multisig() {
+        use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_2, OP_PUSHNUM_3};
+        use bitcoin::script::{Builder, PushBytesBuf};
+
+        let pubkey = |byte: u8| {
+            let mut key = vec![0x02];
+            key.extend([byte; 32]);
+            PushBytesBuf::try_from(key).unwrap()
+        };
+        let redeem_script = Builder::new()
+            .push_opcode(OP_PUSHNUM_2)
+            .push_slice(pubkey(1))
+            .push_slice(pubkey(2))
+            .push_slice(pubkey(3))
+            .push_opcode(OP_PUSHNUM_3)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(multisig_threshold(&redeem_script), Some((2, 3)));
+
+        let signature = PushBytesBuf::try_from(vec![0x30; 71]).unwrap();
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.input[0].witness.clear();
+        tx.input[0].script_sig = Builder::new()
+            .push_int(0)
+            .push_slice(&signature)
+            .push_slice(&signature)
+            .push_slice(PushBytesBuf::try_from(redeem_script.to_bytes()).unwrap())
+            .into_script();
+
+        let multisig = p2sh_multisig(&tx.input[0]).unwrap();
+        assert_eq!((multisig.required, multisig.keys), (2, 3));
+        assert_eq!(multisig.signatures, 2);
+        assert_eq!(multisig.redeem_script, redeem_script);
+
+        let opts = RenderOptions::default().color(false);
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("2-of-3"));
+        assert!(rendered.contains("Signatures provided: 2"));
+
+        // Without the OP_0 dummy CHECKMULTISIG would consume a signature
+        tx.input[0].script_sig = Builder::new()
+            .push_slice(&signature)
+            .push_slice(PushBytesBuf::try_from(redeem_script.to_bytes()).unwrap())
+            .into_script();
+        assert_eq!(p2sh_multisig(&tx.input[0]), None);
+    }
+
+    #[test]
+    fn test_script_timelocks() {
+        use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CLTV, OP_CSV, OP_DROP};
+        use bitcoin::script::Builder;
+
+        let cltv = Builder::new()
+            .push_int(800_000)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_DROP)
+            .push_slice([0x02; 33])
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(script_timelocks(&cltv), vec!["CLTV block height 800000"]);
+
+        // Revealed as the witness script of a P2WSH spend
+        let mut input = decode_transaction(SEGWIT_TX_HEX).unwrap().input[0].clone();
+        input.witness = bitcoin::Witness::from_slice(&[vec![0x30; 71], cltv.to_bytes()]);
+        assert_eq!(revealed_script(&input), Some(cltv.as_script()));
+
+        let csv = Builder::new()
+            .push_int(144)
+            .push_opcode(OP_CSV)
+            .push_opcode(OP_DROP)
+            .into_script();
+        assert_eq!(script_timelocks(&csv), vec!["CSV 144 blocks"]);
+    }
+
+    #[test]
+    fn test_is_standard() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(is_standard(&tx), (true, vec![]));
+
+        tx.version = bitcoin::transaction::Version(4);
+        tx.output[2].value = bitcoin::Amount::from_sat(100);
+        let (standard, reasons) = is_standard(&tx);
+        assert!(!standard);
+        assert_eq!(
+            reasons,
+            vec![
+                "version 4 is non-standard",
+                "output 2 is dust (100 < 294 satoshis)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_pays_to() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let p2tr = "bc1p5akucnl7tasjp7cw0qej6q389hsed54uwham9ucepr4x3lygyz9q0kuvla";
+        let p2wpkh = "bc1q3kejffwyh7pqw9cfzzrhd80wxq5qnn9jvem8v9";
+
+        assert!(output_pays_to(&tx.output[1], p2tr, Network::Bitcoin));
+        assert!(output_pays_to(&tx.output[2], p2wpkh, Network::Bitcoin));
+        assert!(!output_pays_to(&tx.output[2], p2tr, Network::Bitcoin));
+        // Right script, wrong network
+        assert!(!output_pays_to(&tx.output[2], p2wpkh, Network::Testnet));
+        assert!(!output_pays_to(
+            &tx.output[2],
+            "not an address",
+            Network::Bitcoin
+        ));
+    }
+
+    #[test]
+    fn test_taproot_control_block() {
+        let mut input = decode_transaction(SEGWIT_TX_HEX).unwrap().input[0].clone();
+        assert!(taproot_control_block(&input).is_none());
+
+        // Internal key is the generator's x coordinate, with one merkle sibling
+        let internal_key = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let mut control = vec![0xc1];
+        control.extend(hex::decode(internal_key).unwrap());
+        control.extend([0x44; 32]);
+        input.witness = bitcoin::Witness::from_slice(&[vec![0x33; 64], vec![0x51], control]);
+
+        assert_eq!(detect_input_type(&input), InputType::P2trScriptPath);
+        let control = taproot_control_block(&input).unwrap();
+        assert_eq!(control.internal_key.to_string(), internal_key);
+        assert_eq!(control.output_key_parity, bitcoin::secp256k1::Parity::Odd);
+        assert_eq!(control.merkle_branch.len(), 1);
+    }
+
+    #[test]
+    fn test_render_quiet() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let rendered = render_transaction(
+            &tx,
+            &RenderOptions {
+                color: false,
+                quiet: true,
+                ..RenderOptions::default()
+            },
+        );
+        assert!(rendered.contains("\nTRANSACTION OVERVIEW\n"));
+        assert!(rendered.contains("\nINPUTS (1)\n"));
+        assert!(!rendered.contains('📋'));
+        assert!(!rendered.contains('─'));
+        assert!(!rendered.contains('═'));
+    }
+
+    #[test]
+    fn test_render_options_builder() {
+        let opts = RenderOptions::default()
+            .network(Network::Testnet)
+            .color(false)
+            .verbose(true)
+            .denomination(Denomination::Sat)
+            .max_witness_preview(None);
+        assert_eq!(opts.network, Network::Testnet);
+        assert!(!opts.color);
+        assert!(opts.verbose);
+        assert_eq!(opts.denomination, Denomination::Sat);
+        assert_eq!(opts.max_witness_preview, None);
+        // Untouched options keep their defaults
+        assert_eq!(opts.table_style, TableStyle::Clean);
+        assert!(!opts.quiet);
+    }
+
+    #[test]
+    fn test_likely_change_output() {
+        // The P2WPKH input's type matches only output #2
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(likely_change_output(&tx), Some(2));
+
+        // Same-type outputs: a round payment and non-round change
+        let change_script = tx.output[2].script_pubkey.clone();
+        tx.output = vec![
+            bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(5_000_000),
+                script_pubkey: change_script.clone(),
+            },
+            bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(1_234_567),
+                script_pubkey: change_script,
+            },
+        ];
+        assert_eq!(likely_change_output(&tx), Some(1));
+
+        // Nothing to tell them apart
+        tx.output[0].value = bitcoin::Amount::from_sat(4_321_987);
+        assert_eq!(likely_change_output(&tx), None);
+
+        assert_eq!(
+            likely_change_output(&decode_transaction(COINBASE_TX_HEX).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_taproot_tweak() {
+        use bitcoin::key::TapTweak;
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let internal_key: bitcoin::XOnlyPublicKey =
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+                .parse()
+                .unwrap();
+        let merkle_root = bitcoin::TapNodeHash::from_byte_array([7; 32]);
+        let (output_key, _) = internal_key.tap_tweak(&secp, Some(merkle_root));
+        let output = bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(10_000),
+            script_pubkey: bitcoin::ScriptBuf::new_p2tr_tweaked(output_key),
+        };
+
+        let output_key = taproot_output_key(&output).unwrap();
+        assert!(taproot_tweak_matches(
+            output_key,
+            internal_key,
+            Some(merkle_root)
+        ));
+        assert!(!taproot_tweak_matches(output_key, internal_key, None));
+
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(taproot_output_key(&tx.output[0]).is_none());
+        tx.output[0] = output;
+        let opts = RenderOptions::default()
+            .color(false)
+            .internal_key(Some(internal_key))
+            .merkle_root(Some(merkle_root));
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains(&output_key.to_string()));
+        assert!(rendered.contains("✓ output key matches internal key + merkle root"));
+        // Output #1 is an unrelated P2TR output
+        assert!(rendered.contains("✗ output key does not match"));
+    }
+
+    #[test]
+    fn test_future_witness_version() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(
+            tx.output
+                .iter()
+                .all(|o| future_witness_version(o).is_none())
+        );
+
+        let mut script = vec![0x52, 0x20];
+        script.extend([0xab; 32]);
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::from_bytes(script);
+        assert_eq!(future_witness_version(&tx.output[0]), Some(2));
+        // The P2A anchor is witness v1
+        tx.output[1].script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0x51, 0x02, 0x4e, 0x73]);
+        assert_eq!(future_witness_version(&tx.output[1]), None);
+        // Programs longer than 40 bytes aren't witness programs
+        let mut script = vec![0x60, 0x29];
+        script.extend([0xab; 41]);
+        tx.output[2].script_pubkey = bitcoin::ScriptBuf::from_bytes(script);
+        assert_eq!(future_witness_version(&tx.output[2]), None);
+
+        let rendered = render_transaction(&tx, &RenderOptions::default().color(false));
+        assert!(rendered.contains("Witness v2 program (future/unknown)"));
+        assert!(rendered.contains("bc1z"));
+    }
+
+    #[test]
+    fn test_anyone_can_spend() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(!tx.output.iter().any(is_anyone_can_spend));
+
+        // OP_TRUE
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0x51]);
+        assert!(is_anyone_can_spend(&tx.output[0]));
+        tx.output[1].script_pubkey = bitcoin::ScriptBuf::new();
+        assert!(is_anyone_can_spend(&tx.output[1]));
+        // OP_TRUE followed by data, and the P2A anchor, are not
+        tx.output[2].script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0x51, 0x02, 0x4e, 0x73]);
+        assert!(!is_anyone_can_spend(&tx.output[2]));
+
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        assert!(render_transaction(&tx, &opts).contains("⚠ Anyone-Can-Spend: 0.00120751 BTC"));
+    }
+
+    #[test]
+    fn test_min_relay_fee() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(tx.vsize(), 185);
+        assert_eq!(min_relay_fee(&tx, DEFAULT_MIN_RELAY_FEE_RATE), 185);
+        // Rounded up
+        assert_eq!(min_relay_fee(&tx, 100), 19);
+        assert_eq!(min_relay_fee(&tx, 0), 0);
+        // vsize × rate overflows a u64 before the division by 1,000
+        assert_eq!(min_relay_fee(&tx, u64::MAX), 3_412_647_653_636_267_049);
+
+        let total_output: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        let opts = RenderOptions::default()
+            .color(false)
+            .prevout_values(vec![total_output + 100]);
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("185 sats"));
+        assert!(rendered.contains("⚠ fee is 85 sats below the minimum relay fee"));
+
+        let opts = opts.prevout_values(vec![total_output + 185]);
+        assert!(!render_transaction(&tx, &opts).contains("below the minimum relay fee"));
+    }
+
+    #[test]
+    fn test_sigop_cost() {
+        // One P2WPKH spend, and outputs with no legacy sigops
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(sigop_cost(&tx), 1);
+        let legacy = decode_transaction(
+            "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000",
+        )
+        .unwrap();
+        // The P2PKH output's OP_CHECKSIG, weighing 4
+        assert_eq!(sigop_cost(&legacy), 4);
+
+        // Bare OP_CHECKMULTISIG outputs count 20 legacy sigops each, plus the witness sigop
+        let multisig = bitcoin::ScriptBuf::from_bytes(vec![0xae]);
+        for output in &mut tx.output {
+            output.script_pubkey = multisig.clone();
+        }
+        assert_eq!(sigop_cost(&tx), 3 * 20 * 4 + 1);
+
+        let output = tx.output[0].clone();
+        tx.output = vec![output; 201];
+        assert!(
+            policy_warnings(&tx)[0].starts_with("exceeds standard sigop cost limit: 16081 > 16000")
+        );
+
+        // 5000 witness sigops in a P2WSH script are within the limit, 4001 legacy ones aren't
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        tx.input[0].witness = bitcoin::Witness::from_slice(&[vec![], vec![0xac; 5000]]);
+        assert_eq!(classify_input(&tx.input[0]), InputType::P2wsh);
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0xac; 1000]);
+        assert_eq!(sigop_cost(&tx), 1000 * 4 + 5000);
+        assert!(policy_warnings(&tx).is_empty());
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::from_bytes(vec![0xac; 4001]);
+        assert_eq!(sigop_cost(&tx), 4001 * 4 + 5000);
+        assert!(policy_warnings(&tx)[0].starts_with("exceeds standard sigop cost limit"));
+    }
+
+    #[test]
+    fn test_sanity_warnings() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(sanity_warnings(&tx).is_empty());
+        // The coinbase's zero-value OP_RETURN witness commitment isn't flagged
+        assert!(sanity_warnings(&decode_transaction(COINBASE_TX_HEX).unwrap()).is_empty());
+
+        tx.output[2].value = bitcoin::Amount::ZERO;
+        assert_eq!(
+            sanity_warnings(&tx),
+            ["zero-value non-data output (output 2)"]
+        );
+        let rendered = render_transaction(&tx, &RenderOptions::default().color(false));
+        assert!(rendered.contains("⚠ zero-value non-data output"));
+    }
+
+    #[test]
+    fn test_duplicate_outputs() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(duplicate_outputs(&tx).is_empty());
+
+        // Same script with a different value isn't a duplicate
+        tx.output.push(tx.output[0].clone());
+        tx.output.push(tx.output[0].clone());
+        tx.output.push(bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(1),
+            ..tx.output[2].clone()
+        });
+        assert_eq!(duplicate_outputs(&tx), [(0, 3), (0, 4)]);
+        assert_eq!(
+            sanity_warnings(&tx)[0],
+            "outputs 0 and 3 are identical (same scriptPubKey and value)"
+        );
+    }
+
+    #[test]
+    fn test_policy_warnings() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert!(policy_warnings(&tx).is_empty());
+
+        tx.input[0].witness = bitcoin::Witness::from_slice(&[vec![0u8; 500_000]]);
+        let warnings = policy_warnings(&tx);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("exceeds standard weight limit"));
+        assert!(
+            warnings[0].ends_with("WU > 400000 WU (policy MAX_STANDARD_TX_WEIGHT, not consensus)")
+        );
+        assert_eq!(
+            warnings[1],
+            "exceeds legacy size limit: 500165 bytes > 100000 bytes (pre-SegWit MAX_STANDARD_TX_SIZE, not enforced since Bitcoin Core 0.13)"
+        );
+        assert!(is_standard(&tx).1[0].ends_with("WU exceeds 400000 WU"));
+
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        let rendered = render_transaction(&tx, &opts);
+        assert!(rendered.contains("⚠ exceeds standard weight limit"));
+        assert!(rendered.contains("⚠ exceeds legacy size limit"));
+    }
+
+    #[test]
+    fn test_op_return_warnings() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let data = |len: usize| {
+            let push = bitcoin::script::PushBytesBuf::try_from(vec![0xab; len]).unwrap();
+            bitcoin::script::Builder::new()
+                .push_opcode(bitcoin::opcodes::all::OP_RETURN)
+                .push_slice(push)
+                .into_script()
+        };
+        tx.output[0].script_pubkey = data(80);
+        assert_eq!(tx.output[0].script_pubkey.len(), MAX_OP_RETURN_RELAY);
+        assert_eq!(is_standard(&tx), (true, vec![]));
+
+        tx.output[0].script_pubkey = data(81);
+        let (standard, reasons) = is_standard(&tx);
+        assert!(!standard);
+        assert!(reasons[0].starts_with("output 0 OP_RETURN is 84 bytes (max 83"));
+
+        // Four 20-byte pushes are 80 bytes of data in an 85-byte script
+        let mut builder =
+            bitcoin::script::Builder::new().push_opcode(bitcoin::opcodes::all::OP_RETURN);
+        for _ in 0..4 {
+            builder = builder.push_slice([0xab; 20]);
+        }
+        tx.output[0].script_pubkey = builder.into_script();
+        assert!(is_standard(&tx).1[0].starts_with("output 0 OP_RETURN is 85 bytes"));
+        assert!(policy_warnings(&tx).is_empty());
+
+        tx.output[1].script_pubkey = data(4);
+        assert_eq!(
+            policy_warnings(&tx),
+            [
+                "non-standard: 2 OP_RETURN outputs, default policy allows 1 (Bitcoin Core 30+ relays several)"
+            ]
+        );
+        assert_eq!(is_standard(&tx).1[1], "2 OP_RETURN outputs (max 1)");
+    }
+
+    #[test]
+    fn test_bip34_height() {
+        let mut tx = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert_eq!(bip34_height(&tx), Ok(1000));
+
+        tx.input[0].script_sig = bitcoin::ScriptBuf::from_hex("03a0bb0d").unwrap();
+        assert_eq!(bip34_height(&tx), Ok(900_000));
+        tx.input[0].script_sig = bitcoin::ScriptBuf::from_hex("5a").unwrap();
+        assert_eq!(bip34_height(&tx), Ok(10));
+        // 1000 padded with a zero byte
+        tx.input[0].script_sig = bitcoin::ScriptBuf::from_hex("03e80300").unwrap();
+        assert!(
+            bip34_height(&tx)
+                .unwrap_err()
+                .contains("not a minimally encoded number")
+        );
+        tx.input[0].script_sig = bitcoin::ScriptBuf::from_hex("02e883").unwrap();
+        assert_eq!(
+            bip34_height(&tx),
+            Err("height -1000 is negative".to_string())
+        );
+        tx.input[0].script_sig = bitcoin::ScriptBuf::new();
+        assert!(bip34_height(&tx).is_err());
+
+        let spend = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(
+            bip34_height(&spend),
+            Err("not a coinbase transaction".to_string())
+        );
+
+        let opts = RenderOptions::default()
+            .color(false)
+            .expected_height(Some(1001));
+        let rendered = render_transaction(&decode_transaction(COINBASE_TX_HEX).unwrap(), &opts);
+        assert!(rendered.contains("1000 (BIP34)"));
+        assert!(rendered.contains("✗ mismatch, expected 1001"));
+    }
+
+    #[test]
+    fn test_extract_pubkeys() {
+        let tx = decode_transaction(LEGACY_TX_HEX).unwrap();
+        assert_eq!(
+            extract_pubkeys(&tx),
+            vec![
+                "033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52"
+                    .parse::<bitcoin::PublicKey>()
+                    .unwrap()
+            ]
+        );
+
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let witness_key = tx.input[0].witness.nth(1).unwrap().to_vec();
+        assert_eq!(extract_pubkeys(&tx).len(), 1);
+
+        // The same key again is deduplicated; x = 0 is not on the curve, so is skipped
+        let mut off_curve = [0u8; 33];
+        off_curve[0] = 0x02;
+        tx.input[0].witness.push(&witness_key);
+        tx.input[0].witness.push(off_curve);
+        assert_eq!(extract_pubkeys(&tx).len(), 1);
+
+        // P2PK output
+        tx.output[0].script_pubkey = bitcoin::ScriptBuf::new_p2pk(
+            &"0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(extract_pubkeys(&tx).len(), 2);
+    }
+
+    #[test]
+    fn test_extract_xonly_keys() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let output_key: bitcoin::XOnlyPublicKey =
+            "a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208a"
+                .parse()
+                .unwrap();
+        assert_eq!(extract_xonly_keys(&tx), vec![output_key]);
+
+        // Script path spend revealing an internal key: <arg> <OP_TRUE leaf> <control block>
+        let internal_key: bitcoin::XOnlyPublicKey =
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+                .parse()
+                .unwrap();
+        let mut control_block = vec![0xc0];
+        control_block.extend_from_slice(&internal_key.serialize());
+        tx.input[0].witness =
+            bitcoin::Witness::from_slice(&[vec![0x01], vec![0x51], control_block]);
+        assert_eq!(extract_xonly_keys(&tx), vec![internal_key, output_key]);
+
+        // A P2TR output whose key is off the curve is skipped
+        tx.output[1].script_pubkey =
+            bitcoin::ScriptBuf::from_bytes([[0x51, 0x20].as_slice(), &[0; 32]].concat());
+        assert_eq!(extract_xonly_keys(&tx), vec![internal_key]);
+    }
+
+    #[test]
+    fn test_verify_roundtrip() {
+        let bytes = hex::decode(SEGWIT_TX_HEX).unwrap();
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(verify_roundtrip(&tx, &bytes), Ok(()));
+
+        let fields = serialization_fields(&tx);
+        assert_eq!(fields.last().unwrap().end, bytes.len());
+
+        // The output count written as a 3-byte varint
+        let mut padded = bytes.clone();
+        padded.splice(48..49, [0xfd, 0x03, 0x00]);
+        assert_eq!(
+            verify_roundtrip(&tx, &padded),
+            Err("Non-canonical encoding at byte 48 (output count)".to_string())
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0x00);
+        assert_eq!(
+            verify_roundtrip(&tx, &trailing),
+            Err(format!(
+                "Non-canonical encoding at byte {} (trailing data)",
+                bytes.len()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_id_checks() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let txid = tx.compute_txid();
+        let wtxid = tx.compute_wtxid();
+        assert!(id_checks(&tx, &RenderOptions::default()).is_empty());
+
+        let opts = RenderOptions::default()
+            .expected_txid(Some(txid))
+            .expected_wtxid(Some(wtxid));
+        assert!(id_checks(&tx, &opts).iter().all(|(_, _, ok)| *ok));
+
+        // Same transaction with a different witness
+        let mut malleated = tx.clone();
+        malleated.input[0].witness.push([0u8]);
+        assert_eq!(malleated.compute_txid(), txid);
+        let checks = id_checks(&malleated, &opts);
+        assert!(checks[0].2);
+        assert!(checks[1].1.contains("witness malleation detected"));
+
+        let opts = opts.expected_txid(Some(malleated.input[0].previous_output.txid));
+        let checks = id_checks(&tx, &opts);
+        assert!(checks[0].1.starts_with("✗ mismatch"));
+        let rendered = render_transaction(&tx, &opts.color(false));
+        assert!(rendered.contains(&wtxid.to_string()));
+        assert!(rendered.contains("Expected txid"));
+    }
+
+    #[test]
+    fn test_sighash_warnings() {
+        let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        assert_eq!(input_sighash_types(&tx.input[0]), [EcdsaSighashType::All]);
+        let per_input: Vec<_> = tx.input.iter().map(input_sighash_types).collect();
+        assert!(sighash_warnings(&tx, &per_input).is_empty());
+
+        // Second input signing with SIGHASH_SINGLE while there is only one output
+        let mut input = tx.input[0].clone();
+        let mut items: Vec<Vec<u8>> = input.witness.iter().map(<[u8]>::to_vec).collect();
+        *items[0].last_mut().unwrap() = 0x03;
+        input.witness = bitcoin::Witness::from_slice(&items);
+        tx.input.push(input);
+        tx.output.truncate(1);
+
+        // SegWit signature hashing has no SIGHASH_SINGLE bug
+        let per_input: Vec<_> = tx.input.iter().map(input_sighash_types).collect();
+        assert_eq!(per_input[1], [EcdsaSighashType::Single]);
+        assert_eq!(
+            sighash_warnings(&tx, &per_input),
+            ["mixed sighash types detected"]
+        );
+        assert!(!has_sighash_single_bug(&tx, 1));
+    }
+
+    #[test]
+    fn test_sighash_single_bug() {
+        let mut tx = decode_transaction(LEGACY_TX_HEX).unwrap();
+        assert!(!has_sighash_single_bug(&tx, 0));
+
+        // Second legacy input signing with SIGHASH_SINGLE while there is only one output
+        let mut input = tx.input[0].clone();
+        let mut pushes: Vec<Vec<u8>> = input
+            .script_sig
+            .instructions()
+            .map(|instruction| {
+                instruction
+                    .unwrap()
+                    .push_bytes()
+                    .unwrap()
+                    .as_bytes()
+                    .to_vec()
+            })
+            .collect();
+        *pushes[0].last_mut().unwrap() = 0x03;
+        let mut builder = bitcoin::script::Builder::new();
+        for push in pushes {
+            builder = builder.push_slice(bitcoin::script::PushBytesBuf::try_from(push).unwrap());
+        }
+        input.script_sig = builder.into_script();
+        tx.input.push(input);
+
+        assert!(!has_sighash_single_bug(&tx, 0));
+        assert!(has_sighash_single_bug(&tx, 1));
+        let per_input: Vec<_> = tx.input.iter().map(input_sighash_types).collect();
+        let warnings = sighash_warnings(&tx, &per_input);
+        assert_eq!(
+            warnings[1],
+            "SIGHASH_SINGLE bug: input #1 has no corresponding output"
+        );
+
+        // Flagged on the input even without --verbose
+        let opts = RenderOptions {
+            color: false,
+            ..RenderOptions::default()
+        };
+        assert!(
+            render_transaction(&tx, &opts)
+                .contains("⚠ SIGHASH_SINGLE bug: input #1 has no corresponding output")
+        );
+
+        // A second output gives the input its own output to sign
+        tx.output.push(tx.output[0].clone());
+        assert!(!has_sighash_single_bug(&tx, 1));
+    }
+
+    #[test]
+    fn test_spent_and_created_outpoints() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let spent = spent_outpoints(&tx);
+        assert_eq!(spent.len(), 1);
+        assert_eq!(
+            spent[0].txid.to_string(),
+            "4951fc59cdc76b2e7827416cc9b7ecfaa31facfc4e02bba7b8aadeedbe61eb0e"
+        );
+        assert_eq!(spent[0].vout, 2);
+
+        let txid = tx.compute_txid();
+        let created = created_outpoints(txid, &tx);
+        assert_eq!(created.len(), 3);
+        assert!(created.iter().all(|outpoint| outpoint.txid == txid));
+        assert_eq!(created[2].vout, 2);
+
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+        assert!(spent_outpoints(&coinbase)[0].is_null());
+        assert_eq!(
+            created_outpoints(coinbase.compute_txid(), &coinbase).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_batch_conflicts() {
+        let original = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let mut replacement = original.clone();
+        replacement.output.pop();
+        let unrelated = decode_transaction(NESTED_SEGWIT_TX_HEX).unwrap();
+        let coinbase = decode_transaction(COINBASE_TX_HEX).unwrap();
+
+        assert!(batch_conflicts([(0, &original), (1, &unrelated), (2, &coinbase)]).is_empty());
+        assert!(batch_conflicts([(0, &coinbase), (1, &coinbase)]).is_empty());
+
+        let conflicts = batch_conflicts([(2, &original), (3, &unrelated), (5, &replacement)]);
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                outpoint: original.input[0].previous_output,
+                spenders: vec![2, 5],
+            }]
+        );
+
+        let opts = RenderOptions::default().color(false);
+        assert!(render_conflicts(&conflicts, &opts).contains(&format!(
+            "tx #2 and tx #5 both spend {}",
+            original.input[0].previous_output
+        )));
+    }
+
+    #[test]
+    fn test_package_edges() {
+        let parent = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let mut child = decode_transaction(COINBASE_TX_HEX).unwrap();
+        child.input[0].previous_output = bitcoin::OutPoint::new(parent.compute_txid(), 2);
+
+        let edges = package_edges([(0, &parent), (3, &child)]);
+        assert_eq!(
+            edges,
+            vec![PackageEdge {
+                parent: 0,
+                output: 2,
+                child: 3,
+                input: 0,
+            }]
+        );
+
+        let opts = RenderOptions {
+            color: false,
+            package: Some(PackageContext { index: 0, edges }),
+            ..RenderOptions::default()
+        };
+        assert!(render_transaction(&parent, &opts).contains("spent by tx #3"));
+        assert!(
+            render_package_relationships(&opts.package.as_ref().unwrap().edges, &opts)
+                .contains("input 0 spends output 2 of tx #0")
+        );
+
+        // Outputs the parent doesn't have are not linked
+        child.input[0].previous_output.vout = 7;
+        assert!(package_edges([(0, &parent), (1, &child)]).is_empty());
+    }
+
+    #[test]
+    fn test_compute_fee() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+        let total_output: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        assert_eq!(compute_fee(&tx, &[total_output + 1_000]), Ok(1_000));
+        assert!(compute_fee(&tx, &[total_output - 1]).is_err());
+        assert!(compute_fee(&tx, &[total_output, 0]).is_err());
+
+        // Totals that don't fit in a u64 are errors, not panics
+        let mut two_inputs = tx.clone();
+        two_inputs.input.push(tx.input[0].clone());
+        assert_eq!(
+            compute_fee(&two_inputs, &[u64::MAX, 1]),
+            Err("Total prevout value overflows a 64-bit satoshi count".to_string())
+        );
+        let mut tx = tx;
+        tx.output[0].value = bitcoin::Amount::from_sat(u64::MAX);
+        assert_eq!(
+            compute_fee(&tx, &[u64::MAX]),
+            Err("Total output value overflows a 64-bit satoshi count".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_fee_buckets() {
+        assert_eq!(
+            "5:20:50".parse::<FeeBuckets>(),
+            Ok(FeeBuckets {
+                low: 5.0,
+                medium: 20.0,
+                high: 50.0
+            })
+        );
+        assert!("20:5:50".parse::<FeeBuckets>().is_err());
+        assert!("5:20".parse::<FeeBuckets>().is_err());
+        assert!("a:b:c".parse::<FeeBuckets>().is_err());
+    }
+
+    #[test]
+    fn test_fee_rate_bucket() {
+        let buckets = "5:20:50".parse::<FeeBuckets>().unwrap();
+        assert!(fee_rate_bucket(1.0, &buckets).starts_with("low"));
+        assert!(fee_rate_bucket(5.0, &buckets).starts_with("medium"));
+        assert!(fee_rate_bucket(49.9, &buckets).starts_with("high"));
+        assert!(fee_rate_bucket(50.0, &buckets).starts_with("very high"));
+    }
+
+    #[test]
+    fn test_explain_transaction() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+
+        let without_fee = explain_transaction(&tx, &RenderOptions::default());
+        assert!(without_fee.starts_with("This transaction spends 1 input and creates 3 outputs"));
+        assert!(!without_fee.contains("fee"));
+        assert!(without_fee.contains("Input 0 spends output 2 of transaction"));
+        assert!(without_fee.contains("Output 2 sends 0.00555247 BTC to bc1q"));
+
+        let total_output: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        let opts = RenderOptions {
+            prevout_values: vec![total_output + 1_000],
+            ..RenderOptions::default()
+        };
+        let with_fee = explain_transaction(&tx, &opts);
+        assert!(with_fee.contains("paying a fee of 0.00001000 BTC"));
+    }
+
+    #[test]
+    fn test_vsize_sum_plus_overhead_equals_tx_vsize() {
+        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+
+        let inputs_vsize: usize = tx.input.iter().map(input_vsize).sum();
+        let outputs_vsize: usize = tx.output.iter().map(output_vsize).sum();
+
+        // Transaction overhead:
+        // base: version(4) + input_count(1) + output_count(1) + locktime(4) = 10
+        // witness: marker(1) + flag(1) = 2
+        // overhead weight = 10*4 + 2 = 42, overhead vsize = ceil(42/4) = 11
+        let overhead_base =
+            4 + compact_size_len(tx.input.len()) + compact_size_len(tx.output.len()) + 4;
+        let overhead_witness = 2; // segwit marker + flag
+        let overhead_weight = overhead_base * 4 + overhead_witness;
+        let overhead_vsize = overhead_weight.div_ceil(4);
+
+        assert_eq!(inputs_vsize + outputs_vsize + overhead_vsize, tx.vsize());
+    }
+}
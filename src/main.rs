@@ -1,542 +1,1073 @@
 // Copyright (c) 2025 Oleg Kubrakov
 
-use bitcoin::{Transaction, consensus::encode};
-use clap::Parser;
+use bitcoin::consensus::encode::{serialize, serialize_hex};
+use bitcoin::{Transaction, TxOut};
+use bitcoin_tx_decoder::{
+    Confirmation, DEFAULT_MAX_TX_SIZE, DEFAULT_MAX_WITNESS_PREVIEW, DEFAULT_MIN_RELAY_FEE_RATE,
+    Denomination, FeeBuckets, OutputOrder, PackageContext, RenderOptions, TableStyle, annotate_hex,
+    batch_conflicts, compute_fee, core_decode, decode_bech32_payload, decode_hex_limited,
+    decode_psbt, decode_transaction_bytes, decode_transaction_limited, decode_transaction_list,
+    explain_transaction, follow_spend, package_edges, psbt_input_amounts, render_conflicts,
+    render_package_relationships, render_summary_table, strip_hex, strip_witness, verify_roundtrip,
+    write_transaction,
+};
+use clap::{CommandFactory, Parser, ValueEnum, error::ErrorKind};
 use colored::*;
-use prettytable::{Cell, Row, Table, format};
+use flate2::read::GzDecoder;
+use notify::{RecursiveMode, Watcher};
 use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod esplora;
+mod rpc;
+
+/// Encoding of the transaction file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    /// Hex strings, one transaction per line
+    Hex,
+    /// A single raw serialized transaction
+    #[value(alias = "raw-binary")]
+    Binary,
+}
+
+/// Text encoding of the transactions given with --tx or in a hex --file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Encoding {
+    Hex,
+    /// Transaction bytes as the data part of a bech32 or bech32m string
+    Bech32,
+}
+
+/// Process exit codes, listed in `--help`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Exit {
+    Failure = 1,
+    InvalidHex = 2,
+    DecodeFailed = 3,
+    Io = 4,
+    NoInput = 5,
+}
+
+impl Exit {
+    fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+
+    /// Tell apart malformed hex from well-formed hex that isn't a transaction
+    fn for_decode_error(tx_hex: &str) -> Self {
+        if hex::decode(strip_hex(tx_hex)).is_err() {
+            Exit::InvalidHex
+        } else {
+            Exit::DecodeFailed
+        }
+    }
+}
+
+/// How decoded transactions are printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Colored tables
+    Table,
+    /// JSON matching `bitcoin-cli decoderawtransaction`, indented on a terminal and
+    /// compact when piped
+    CoreJson,
+    /// Like core-json, always indented
+    CoreJsonPretty,
+    /// Like core-json, always on a single line
+    CoreJsonCompact,
+    /// The core-json fields as binary MessagePack, for piping into other programs
+    Msgpack,
+}
+
+impl OutputFormat {
+    /// Whether transactions are serialized from the `bitcoin-cli` model instead of
+    /// rendered as tables
+    fn is_serialized(self) -> bool {
+        self != OutputFormat::Table
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(name = "Bitcoin Transaction Decoder")]
-#[command(author, version, about = "Decode and visualize Bitcoin transactions beautifully", long_about = None)]
+#[command(
+    author,
+    version,
+    about = "Decode and visualize Bitcoin transactions beautifully",
+    long_about = "Decode and visualize Bitcoin transactions beautifully\n\n\
+        Exit codes:\n  \
+        0  success\n  \
+        1  any other failure (bad option value, fee mismatch, RPC error)\n  \
+        2  invalid hex, or invalid command-line arguments\n  \
+        3  transaction failed to decode\n  \
+        4  file could not be read\n  \
+        5  no transaction provided"
+)]
 struct Args {
-    /// Hex-encoded transaction string
-    #[arg(short, long, value_name = "HEX", conflicts_with = "file")]
-    tx: Option<String>,
-
-    /// File containing hex-encoded transaction
+    /// Hex-encoded transaction strings, separated by spaces or commas
+    #[arg(
+        short,
+        long,
+        value_name = "HEX",
+        num_args = 1..,
+        value_delimiter = ',',
+        conflicts_with = "file"
+    )]
+    tx: Vec<String>,
+
+    /// File containing hex-encoded transactions, one per line
     #[arg(short, long, value_name = "FILE", conflicts_with = "tx")]
     file: Option<String>,
+
+    /// Hex of the transaction whose output the --tx transaction spends, used to show the
+    /// prevout and compute the fee
+    #[arg(long, value_name = "PREV_TX_HEX", requires = "tx")]
+    follow_spend: Option<String>,
+
+    /// PSBT, base64 or hex, whose unsigned transaction is decoded with its input amounts
+    #[arg(long, value_name = "PSBT", conflicts_with_all = ["tx", "file", "txid"])]
+    psbt: Option<String>,
+
+    /// Encoding of the --file contents
+    #[arg(long, value_enum, default_value = "hex", requires = "file")]
+    input_format: InputFormat,
+
+    /// Gunzip the --file contents even without a .gz extension or gzip magic bytes
+    #[arg(long, requires = "file")]
+    gzip: bool,
+
+    /// Transaction ID to fetch from a Bitcoin Core node or an Esplora server
+    #[arg(long, value_name = "TXID", conflicts_with_all = ["tx", "file"], requires = "txid_source")]
+    txid: Option<String>,
+
+    /// Bitcoin Core JSON-RPC endpoint, e.g. http://127.0.0.1:8332
+    #[arg(long, value_name = "URL", requires = "txid", group = "txid_source")]
+    rpc_url: Option<String>,
+
+    /// Esplora REST API base URL, e.g. https://blockstream.info/api; also reports
+    /// whether the --txid transaction is confirmed
+    #[arg(
+        long,
+        value_name = "BASE_URL",
+        requires = "txid",
+        group = "txid_source"
+    )]
+    esplora: Option<String>,
+
+    /// Confirmation status fetched with --esplora
+    #[arg(skip)]
+    confirmation: Option<Confirmation>,
+
+    /// Cookie file used to authenticate against the node
+    #[arg(long, value_name = "FILE", conflicts_with = "rpc_auth")]
+    rpc_cookie: Option<String>,
+
+    /// RPC credentials used to authenticate against the node
+    #[arg(long, value_name = "USER:PASS")]
+    rpc_auth: Option<String>,
+
+    /// Values in satoshis of the outputs spent by each input, comma-separated
+    #[arg(long, value_name = "SATS", value_delimiter = ',')]
+    prevout_values: Vec<u64>,
+
+    /// Fee-rate thresholds in sat/vB separating the low, medium, high and very high buckets
+    #[arg(long, value_name = "LOW:MED:HIGH", default_value = "5:20:50")]
+    fee_buckets: FeeBuckets,
+
+    /// Minimum relay fee rate in sat/kvB used for the "Min Relay Fee" check
+    #[arg(long, value_name = "SAT_PER_KVB", default_value_t = DEFAULT_MIN_RELAY_FEE_RATE)]
+    min_relay_fee_rate: u64,
+
+    /// Also show the txid in internal (little-endian) byte order
+    #[arg(long)]
+    show_internal_hash: bool,
+
+    /// Shorten hex and ASM values longer than N characters
+    #[arg(long, value_name = "N")]
+    truncate: Option<usize>,
+
+    /// Unit for output values and summary totals: btc, mbtc, bits or sat
+    #[arg(long, value_name = "UNIT", default_value = "btc")]
+    denomination: Denomination,
+
+    /// Table borders: clean, box or markdown
+    #[arg(long, value_name = "STYLE", default_value = "clean")]
+    table_style: TableStyle,
+
+    /// Mark outputs that pay to this address
+    #[arg(long, value_name = "ADDR")]
+    highlight_address: Option<String>,
+
+    /// Show only the first and last N/2 bytes of longer witness items; 0 shows them in full
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_WITNESS_PREVIEW)]
+    max_witness_preview: usize,
+
+    /// Show the Electrum scripthash of every output
+    #[arg(long)]
+    electrum: bool,
+
+    /// Assume nodes run a full-RBF policy when describing replaceability (--verbose)
+    #[arg(long)]
+    full_rbf: bool,
+
+    /// Render only the first N inputs; the summary still counts all of them
+    #[arg(long, value_name = "N")]
+    max_inputs_display: Option<usize>,
+
+    /// Render only the first N outputs; the summary still counts all of them
+    #[arg(long, value_name = "N")]
+    max_outputs_display: Option<usize>,
+
+    /// Order to render outputs in: none, value-asc or value-desc. Each output keeps its
+    /// original index; the transaction itself isn't reordered.
+    #[arg(
+        long,
+        value_name = "ORDER",
+        default_value = "none",
+        alias = "sort-outputs-by"
+    )]
+    sort_display_by: OutputOrder,
+
+    /// Add a histogram of output values to the summary
+    #[arg(long)]
+    histogram: bool,
+
+    /// Print one dense table of key facts per transaction instead of the full report;
+    /// the default when decoding several transactions
+    #[arg(long, visible_alias = "compact", conflicts_with_all = ["explain", "annotate_hex"])]
+    summary_table: bool,
+
+    /// Render every transaction of a batch in full instead of the summary table
+    #[arg(long, conflicts_with = "summary_table")]
+    no_summary_table: bool,
+
+    /// Print the raw transaction hex with each serialized field color-coded
+    #[arg(long, conflicts_with = "explain")]
+    annotate_hex: bool,
+
+    /// Print the time spent decoding hex, deserializing and rendering to stderr
+    #[arg(long)]
+    timing: bool,
+
+    /// Reject transactions whose bytes aren't their canonical serialization
+    #[arg(long)]
+    strict: bool,
+
+    /// Only validate and print the canonical transaction hex
+    #[arg(long, conflicts_with = "explain")]
+    hex_only: bool,
+
+    /// Only print the legacy serialization of each transaction, without witness data,
+    /// which is the preimage of its txid
+    #[arg(long, visible_alias = "no-witness", conflicts_with_all = ["explain", "hex_only"])]
+    strip_witness: bool,
+
+    /// Only print the txid of each transaction, one per line
+    #[arg(long, conflicts_with_all = ["explain", "hex_only", "strip_witness"])]
+    txid_only: bool,
+
+    /// Only print the wtxid of each transaction, one per line
+    #[arg(long, conflicts_with_all = ["explain", "hex_only", "strip_witness", "txid_only"])]
+    wtxid_only: bool,
+
+    /// Narrate the transaction in plain English instead of rendering tables
+    #[arg(long, conflicts_with = "format")]
+    explain: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    format: OutputFormat,
+
+    /// Network used to derive output addresses
+    #[arg(long, value_name = "NETWORK", default_value = "bitcoin")]
+    network: bitcoin::Network,
+
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
+
+    /// Print satoshi counts without thousands separators, e.g. 100000000
+    #[arg(long)]
+    no_thousands_sep: bool,
+
+    /// Txid the transaction should have; the report shows whether it matches
+    #[arg(long, value_name = "TXID")]
+    expected_txid: Option<bitcoin::Txid>,
+
+    /// Wtxid the transaction should have, to detect witness malleation
+    #[arg(long, value_name = "WTXID")]
+    expected_wtxid: Option<bitcoin::Wtxid>,
+
+    /// Block height a coinbase transaction should commit to (BIP34)
+    #[arg(long, value_name = "N")]
+    expected_height: Option<u64>,
+
+    /// X-only internal key (hex) to verify P2TR output keys against
+    #[arg(long, value_name = "XONLY_HEX")]
+    internal_key: Option<bitcoin::XOnlyPublicKey>,
+
+    /// Script tree merkle root (hex) committed to with --internal-key
+    #[arg(long, value_name = "HEX", requires = "internal_key")]
+    merkle_root: Option<bitcoin::TapNodeHash>,
+
+    /// Draw a bar next to each output value, scaled to the largest output
+    #[arg(long, value_name = "WIDTH", num_args = 0..=1, default_missing_value = "20")]
+    value_bars: Option<usize>,
+
+    /// Leave out emojis and separator lines around section titles
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Show additional explanatory sections
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Show decoding progress on stderr when the file holds several transactions
+    #[arg(long)]
+    progress: bool,
+
+    /// Diagnostic logging on stderr: error, warn, info or debug
+    #[arg(long, value_name = "LEVEL", default_value = "warn")]
+    log_level: log::LevelFilter,
+
+    /// Text encoding of each transaction
+    #[arg(
+        long,
+        value_enum,
+        default_value = "hex",
+        conflicts_with = "skip_count_prefix"
+    )]
+    encoding: Encoding,
+
+    /// Read a compact-size transaction count, then that many back-to-back transactions
+    #[arg(long)]
+    skip_count_prefix: bool,
+
+    /// Reject transactions larger than this many bytes before decoding them
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_TX_SIZE)]
+    max_size: usize,
+
+    /// Re-render the file every time it changes
+    #[arg(long, requires = "file")]
+    watch: bool,
+
+    /// When decoding several transactions, report outpoints spent by more than one of them
+    #[arg(long)]
+    diff_prevout_set: bool,
 }
 
 fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
 
-    // Get transaction hex from either argument or file
-    let tx_hex = if let Some(hex_str) = args.tx {
-        hex_str
-    } else if let Some(file_path) = args.file {
-        fs::read_to_string(&file_path)
-            .unwrap_or_else(|_| {
-                eprintln!("{} Failed to read file: {}", "✗".red().bold(), file_path);
-                std::process::exit(1);
+    env_logger::Builder::new()
+        .filter_level(args.log_level)
+        .init();
+
+    if args.no_color {
+        colored::control::set_override(false);
+    }
+
+    if let Some(address) = &args.highlight_address
+        && let Err(e) = address
+            .parse::<bitcoin::Address<_>>()
+            .map_err(|e| e.to_string())
+            .and_then(|a| {
+                a.require_network(args.network)
+                    .map_err(|_| format!("not a {} address", args.network))
             })
-            .trim()
-            .to_string()
+    {
+        eprintln!(
+            "{} Invalid --highlight-address {}: {}",
+            "✗".red().bold(),
+            address,
+            e
+        );
+        Exit::Failure.exit();
+    }
+
+    if let Some(psbt) = &args.psbt {
+        if let Err(code) = display_psbt(psbt, &args) {
+            code.exit();
+        }
+        return;
+    }
+
+    // Get transaction hex from either argument or file
+    let tx_hexes = if !args.tx.is_empty() {
+        // A quoted list such as --tx "hex1 hex2" arrives as a single value
+        args.tx
+            .iter()
+            .flat_map(|value| value.split_whitespace())
+            .map(str::to_string)
+            .collect()
+    } else if let Some(file_path) = &args.file {
+        if args.watch {
+            watch_file(file_path, &args);
+        }
+        let contents =
+            read_tx_file(file_path, args.input_format, args.gzip).unwrap_or_else(|(code, e)| {
+                eprintln!("{} {}", "✗".red().bold(), e);
+                code.exit();
+            });
+        if let Err(code) = display_file(&contents, &args) {
+            code.exit();
+        }
+        return;
+    } else if let (Some(txid), Some(rpc_url)) = (&args.txid, &args.rpc_url) {
+        let auth = match (&args.rpc_cookie, &args.rpc_auth) {
+            (Some(cookie), _) => rpc::RpcAuth::Cookie(cookie.clone()),
+            (_, Some(creds)) => rpc::RpcAuth::UserPass(creds.clone()),
+            _ => rpc::RpcAuth::None,
+        };
+        let hex_str = rpc::get_raw_transaction(rpc_url, &auth, txid).unwrap_or_else(|e| {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            Exit::Failure.exit();
+        });
+        vec![hex_str]
+    } else if let (Some(txid), Some(base_url)) = (&args.txid, &args.esplora) {
+        let fetched = esplora::get_raw_transaction(base_url, txid)
+            .and_then(|hex| Ok((hex, esplora::get_confirmation(base_url, txid)?)));
+        let (hex_str, confirmation) = fetched.unwrap_or_else(|e| {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            Exit::Failure.exit();
+        });
+        args.confirmation = Some(confirmation);
+        vec![hex_str]
     } else {
         eprintln!(
             "{}",
-            "Error: Please provide either --tx <HEX> or --file <FILE>"
+            "Error: Please provide either --tx <HEX>, --file <FILE> or --txid <TXID>"
                 .red()
                 .bold()
         );
         eprintln!("\nUsage examples:");
         eprintln!("  btc-tx-decoder --tx <hex-string>");
         eprintln!("  btc-tx-decoder --file transaction.txt");
-        std::process::exit(1);
+        eprintln!(
+            "  btc-tx-decoder --txid <txid> --rpc-url http://127.0.0.1:8332 --rpc-cookie ~/.bitcoin/.cookie"
+        );
+        eprintln!("  btc-tx-decoder --txid <txid> --esplora https://blockstream.info/api");
+        Exit::NoInput.exit();
     };
 
-    // Decode transaction
-    let tx = decode_transaction(&tx_hex).unwrap_or_else(|e| {
-        eprintln!("{} {}", "✗".red().bold(), e);
-        std::process::exit(1);
-    });
+    if let Err(code) = display(&tx_hexes, &args) {
+        code.exit();
+    }
+}
 
-    display_transaction(&tx);
+/// Transactions read from a file
+enum TxFile {
+    /// One hex string per line
+    Hex(Vec<String>),
+    /// A single raw serialized transaction
+    Binary(Vec<u8>),
 }
 
-/// Decode a hex-encoded Bitcoin transaction
-pub fn decode_transaction(hex: &str) -> Result<Transaction, String> {
-    let tx_bytes = hex::decode(hex.trim()).map_err(|e| format!("Invalid hex string: {}", e))?;
+/// Read a file holding one hex-encoded transaction per line, or a single raw
+/// transaction. Gzip-compressed files, recognised by their extension or magic bytes,
+/// are decompressed first.
+fn read_tx_file(
+    file_path: &str,
+    input_format: InputFormat,
+    gzip: bool,
+) -> Result<TxFile, (Exit, String)> {
+    let mut bytes = fs::read(file_path)
+        .map_err(|_| (Exit::Io, format!("Failed to read file: {}", file_path)))?;
+    if gzip || file_path.ends_with(".gz") || bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|e| {
+                (
+                    Exit::Io,
+                    format!("Failed to decompress gzip file {}: {}", file_path, e),
+                )
+            })?;
+        bytes = decompressed;
+    }
+    if input_format == InputFormat::Binary {
+        return Ok(TxFile::Binary(bytes));
+    }
 
-    encode::deserialize(&tx_bytes).map_err(|e| format!("Failed to decode transaction: {}", e))
+    let contents = String::from_utf8(bytes).map_err(|_| {
+        (
+            Exit::Io,
+            format!("File is not valid UTF-8 text: {}", file_path),
+        )
+    })?;
+    let hexes: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    if hexes.is_empty() {
+        return Err((
+            Exit::NoInput,
+            format!("No transaction found in file: {}", file_path),
+        ));
+    }
+    Ok(TxFile::Hex(hexes))
 }
 
-/// Calculate the byte length of a Bitcoin compact size (varint) encoding
-fn compact_size_len(n: usize) -> usize {
-    if n <= 0xfc {
-        1
-    } else if n <= 0xffff {
-        3
-    } else if n <= 0xffff_ffff {
-        5
-    } else {
-        9
+/// Decode and print the transactions read from a file
+fn display_file(contents: &TxFile, args: &Args) -> Result<(), Exit> {
+    match contents {
+        TxFile::Hex(tx_hexes) => display(tx_hexes, args),
+        TxFile::Binary(tx_bytes) => display_binary(tx_bytes, args),
     }
 }
 
-/// Calculate the virtual size of a single input (including its witness data)
-fn input_vsize(input: &bitcoin::TxIn) -> usize {
-    // Non-witness (base) data:
-    // previous_output: txid (32) + vout (4) = 36
-    // script_sig: compact_size(len) + script bytes
-    // sequence: 4
-    let script_sig_len = input.script_sig.len();
-    let base_size = 36 + compact_size_len(script_sig_len) + script_sig_len + 4;
-
-    // Witness data (scaled at 1/4 weight)
-    let witness_size = if !input.witness.is_empty() {
-        let mut size = compact_size_len(input.witness.len()); // number of witness items
-        for item in input.witness.iter() {
-            size += compact_size_len(item.len()) + item.len();
-        }
-        size
-    } else {
-        0
-    };
+/// Decode and print a raw serialized transaction
+fn display_binary(tx_bytes: &[u8], args: &Args) -> Result<(), Exit> {
+    if tx_bytes.len() > args.max_size {
+        eprintln!(
+            "{} Transaction is {} bytes, exceeding the {} byte limit",
+            "✗".red().bold(),
+            tx_bytes.len(),
+            args.max_size
+        );
+        return Err(Exit::DecodeFailed);
+    }
+    if echoes_only(args) {
+        let tx = decode_transaction_bytes(tx_bytes).map_err(|e| {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            Exit::DecodeFailed
+        })?;
+        echo_transaction(&tx, tx_bytes, args);
+        return Ok(());
+    }
+    display_bytes(tx_bytes, None, args)
+}
 
-    let weight = base_size * 4 + witness_size;
-    (weight + 3) / 4 // ceil(weight / 4)
+/// Decode and print the transactions, failing with the exit code of the first error
+fn display(tx_hexes: &[String], args: &Args) -> Result<(), Exit> {
+    if args.encoding == Encoding::Bech32 {
+        let payloads = tx_hexes
+            .iter()
+            .map(|text| decode_bech32_payload(text))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                eprintln!("{} {}", "✗".red().bold(), e);
+                Exit::DecodeFailed
+            })?;
+        // Keep the payload bytes as they are so that --strict and --max-size see the input
+        return match payloads.as_slice() {
+            [tx_bytes] => display_binary(tx_bytes, args),
+            _ => display_unprefixed(&payloads.iter().map(hex::encode).collect::<Vec<_>>(), args),
+        };
+    }
+    if args.skip_count_prefix {
+        return match split_count_prefixed(tx_hexes) {
+            Ok(split) => display_unprefixed(&split, args),
+            Err((code, e)) => {
+                eprintln!("{} {}", "✗".red().bold(), e);
+                Err(code)
+            }
+        };
+    }
+    display_unprefixed(tx_hexes, args)
 }
 
-/// Calculate the virtual size of a single output
-fn output_vsize(output: &bitcoin::TxOut) -> usize {
-    // Outputs are entirely non-witness data:
-    // value: 8 bytes
-    // script_pubkey: compact_size(len) + script bytes
-    let script_len = output.script_pubkey.len();
-    8 + compact_size_len(script_len) + script_len
+/// Split a count-prefixed blob into one hex string per transaction
+fn split_count_prefixed(tx_hexes: &[String]) -> Result<Vec<String>, (Exit, String)> {
+    let bytes = hex::decode(tx_hexes.concat())
+        .map_err(|e| (Exit::InvalidHex, format!("Invalid hex string: {}", e)))?;
+    let list = decode_transaction_list(&bytes).map_err(|e| (Exit::DecodeFailed, e))?;
+    if let Some(warning) = list.warning() {
+        eprintln!("{} {}", "⚠".yellow().bold(), warning);
+    }
+    if list.transactions.is_empty() {
+        return Err((
+            Exit::NoInput,
+            "No transaction found after the count prefix".to_string(),
+        ));
+    }
+    Ok(list.transactions.iter().map(serialize_hex).collect())
 }
 
-fn decode_witness_item(witness: &[u8]) -> String {
-    let len = witness.len();
-
-    match len {
-        0 => "Empty witness".to_string(),
-        1..=75 => {
-            // Likely a signature or public key
-            if len == 33 || len == 65 {
-                "Public Key".to_string()
-            } else if len >= 70 && len <= 73 {
-                "Signature (DER)".to_string()
-            } else if len == 64 {
-                "Signature (Schnorr)".to_string()
-            } else {
-                format!("Data ({} bytes)", len)
-            }
+fn display_unprefixed(tx_hexes: &[String], args: &Args) -> Result<(), Exit> {
+    if echoes_only(args) {
+        return echo_transactions(tx_hexes, args);
+    }
+
+    if let [tx_hex] = tx_hexes {
+        let started = Instant::now();
+        let tx_bytes = decode_hex_limited(tx_hex, args.max_size).map_err(|e| {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            Exit::for_decode_error(tx_hex)
+        })?;
+        let hex_time = started.elapsed();
+        display_bytes(&tx_bytes, Some(hex_time), args)
+    } else {
+        // A block has a single coinbase, so one expected height can't apply to a batch, and
+        // neither can one expected txid or wtxid
+        let single_only = [
+            ("--expected-height", args.expected_height.is_some()),
+            ("--expected-txid", args.expected_txid.is_some()),
+            ("--expected-wtxid", args.expected_wtxid.is_some()),
+        ];
+        if let Some((flag, _)) = single_only.iter().find(|(_, given)| *given) {
+            Args::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("{} can only be used with a single transaction", flag),
+                )
+                .exit();
         }
-        _ => {
-            // Could be a script
-            if len > 100 {
-                format!("Script or Data ({} bytes)", len)
-            } else {
-                format!("Data ({} bytes)", len)
-            }
+        if !args.prevout_values.is_empty() {
+            eprintln!(
+                "{} --prevout-values is ignored when decoding several transactions",
+                "⚠".yellow().bold()
+            );
         }
+        if args.timing {
+            eprintln!(
+                "{} --timing is ignored when decoding several transactions",
+                "⚠".yellow().bold()
+            );
+        }
+        // Progress goes to stderr so it never mixes with the rendered output
+        let show_progress = args.progress || !io::stdout().is_terminal();
+        let opts = render_options(args, Vec::new());
+        decode_batch(tx_hexes, &opts, args, show_progress)
     }
 }
 
-/// Check if an output is a Pay-to-Anchor (P2A) / Ephemeral Anchor output
-/// P2A is OP_1 <0x4e73> (witness v1 with 2-byte program 0x4e73)
-fn is_ephemeral_anchor(output: &bitcoin::TxOut) -> bool {
-    let script_bytes = output.script_pubkey.as_bytes();
-    // P2A: OP_1 (0x51) followed by push of 2 bytes (0x02) then 0x4e73
-    script_bytes.len() == 4
-        && script_bytes[0] == 0x51  // OP_1 (witness version 1)
-        && script_bytes[1] == 0x02  // Push 2 bytes
-        && script_bytes[2] == 0x4e
-        && script_bytes[3] == 0x73
-}
+/// Decode and print a single serialized transaction. `hex_time` is how long it took to
+/// decode the hex it was given as, if any, for --timing.
+fn display_bytes(tx_bytes: &[u8], hex_time: Option<Duration>, args: &Args) -> Result<(), Exit> {
+    let started = Instant::now();
+    let tx = decode_transaction_bytes(tx_bytes).map_err(|e| {
+        eprintln!("{} {}", "✗".red().bold(), e);
+        Exit::DecodeFailed
+    })?;
+    let deserialize_time = started.elapsed();
 
-/// Detect the input type based on witness data
-fn detect_input_type(input: &bitcoin::TxIn) -> String {
-    // Check if it's a SegWit input by examining witness data
-    if !input.witness.is_empty() {
-        let witness_count = input.witness.len();
-
-        // P2WPKH (Pay-to-Witness-Public-Key-Hash)
-        // Witness stack: <signature> <pubkey>
-        if witness_count == 2 {
-            let pubkey_len = input.witness.nth(1).map(|w| w.len()).unwrap_or(0);
-            if pubkey_len == 33 || pubkey_len == 65 {
-                return "P2WPKH (Pay-to-Witness-Public-Key-Hash)".to_string();
-            }
-        }
+    check_roundtrip(&tx, tx_bytes, args.strict, "")?;
 
-        // P2WSH (Pay-to-Witness-Script-Hash)
-        // Witness stack: <item1> <item2> ... <witness_script>
-        // Last item is the actual script being satisfied
-        if witness_count >= 2 {
-            let last_item_len = input.witness.last().map(|w| w.len()).unwrap_or(0);
-            // P2WSH witness scripts are typically larger
-            if last_item_len > 33 {
-                return "P2WSH (Pay-to-Witness-Script-Hash)".to_string();
-            }
-        }
+    if args.expected_height.is_some() && !tx.is_coinbase() {
+        eprintln!(
+            "{} --expected-height: transaction {} is not a coinbase",
+            "✗".red().bold(),
+            tx.compute_txid()
+        );
+        return Err(Exit::Failure);
+    }
 
-        // P2TR (Pay-to-Taproot)
-        // Key path spend: single 64-65 byte signature
-        // Script path spend: multiple items with control block
-        if witness_count == 1 {
-            let sig_len = input.witness.nth(0).map(|w| w.len()).unwrap_or(0);
-            if sig_len == 64 || sig_len == 65 {
-                return "P2TR (Pay-to-Taproot) - Key Path Spend".to_string();
-            }
-        } else if witness_count >= 2 {
-            // Check for control block (starts with 0xc0 or 0xc1)
-            if let Some(last_item) = input.witness.last() {
-                if !last_item.is_empty() && (last_item[0] == 0xc0 || last_item[0] == 0xc1) {
-                    return "P2TR (Pay-to-Taproot) - Script Path Spend".to_string();
-                }
-            }
-        }
+    let spent_outputs = match &args.follow_spend {
+        Some(prev_hex) => follow_prevout(&tx, prev_hex, args.max_size)?,
+        None => Vec::new(),
+    };
+    let amounts: Vec<Option<u64>> = spent_outputs
+        .iter()
+        .map(|output| Some(output.as_ref()?.value.to_sat()))
+        .collect();
+    let prevout_values = if args.prevout_values.is_empty() {
+        amounts
+            .iter()
+            .copied()
+            .collect::<Option<Vec<u64>>>()
+            .unwrap_or_default()
+    } else {
+        args.prevout_values.clone()
+    };
 
-        return "SegWit (Unknown type)".to_string();
+    if !prevout_values.is_empty()
+        && let Err(e) = compute_fee(&tx, &prevout_values)
+    {
+        eprintln!("{} {}", "✗".red().bold(), e);
+        return Err(Exit::Failure);
     }
 
-    // Legacy input types
-    if !input.script_sig.is_empty() {
-        let script_len = input.script_sig.len();
+    let opts = render_options(args, prevout_values)
+        .input_amounts(amounts)
+        .spent_outputs(spent_outputs);
+    let started = Instant::now();
+    print_transaction(&tx, &opts, args);
+    let render_time = started.elapsed();
 
-        // P2PKH typically has ~107 byte scriptSig
-        if script_len > 100 && script_len < 150 {
-            return "P2PKH (Pay-to-Public-Key-Hash) - Legacy".to_string();
+    if args.timing {
+        if let Some(hex_time) = hex_time {
+            eprintln!("⏱ hex decoding     {:?}", hex_time);
         }
+        eprintln!("⏱ deserialization  {:?}", deserialize_time);
+        eprintln!("⏱ rendering        {:?}", render_time);
+    }
+    check_expected_ids(&tx, args)
+}
 
-        // P2SH can vary widely
-        if script_len > 0 {
-            return "P2SH or Legacy".to_string();
-        }
+/// Fail when the transaction's ids differ from --expected-txid or --expected-wtxid. The
+/// report already shows which one differs.
+fn check_expected_ids(tx: &Transaction, args: &Args) -> Result<(), Exit> {
+    let txid_differs = args
+        .expected_txid
+        .is_some_and(|txid| txid != tx.compute_txid());
+    let wtxid_differs = args
+        .expected_wtxid
+        .is_some_and(|wtxid| wtxid != tx.compute_wtxid());
+    if txid_differs || wtxid_differs {
+        Err(Exit::Failure)
+    } else {
+        Ok(())
     }
+}
 
-    "Unknown".to_string()
+/// Warn when the input bytes aren't the canonical serialization of `tx`, or fail under --strict
+fn check_roundtrip(
+    tx: &Transaction,
+    tx_bytes: &[u8],
+    strict: bool,
+    label: &str,
+) -> Result<(), Exit> {
+    match verify_roundtrip(tx, tx_bytes) {
+        Ok(()) => Ok(()),
+        Err(e) if strict => {
+            eprintln!("{} {}{}", "✗".red().bold(), label, e);
+            Err(Exit::DecodeFailed)
+        }
+        Err(e) => {
+            eprintln!(
+                "{} {}{}, rendering the canonical form (pass --strict to reject it)",
+                "⚠".yellow().bold(),
+                label,
+                e
+            );
+            Ok(())
+        }
+    }
 }
 
-/// Get the address type as a human-readable string
-fn get_address_type(address: &bitcoin::Address) -> &'static str {
-    use bitcoin::address::AddressType;
+/// Decode the --follow-spend transaction and pair its outputs with the inputs spending them
+fn follow_prevout(
+    tx: &Transaction,
+    prev_hex: &str,
+    max_size: usize,
+) -> Result<Vec<Option<TxOut>>, Exit> {
+    let prev_tx = decode_transaction_limited(prev_hex, max_size).map_err(|e| {
+        eprintln!("{} --follow-spend: {}", "✗".red().bold(), e);
+        Exit::for_decode_error(prev_hex)
+    })?;
+    follow_spend(tx, &prev_tx).map_err(|e| {
+        eprintln!("{} --follow-spend: {}", "✗".red().bold(), e);
+        Exit::Failure
+    })
+}
 
-    match address.address_type() {
-        Some(AddressType::P2pkh) => "P2PKH",
-        Some(AddressType::P2sh) => "P2SH",
-        Some(AddressType::P2wpkh) => "P2WPKH",
-        Some(AddressType::P2wsh) => "P2WSH",
-        Some(AddressType::P2tr) => "P2TR",
-        Some(AddressType::P2a) => "P2A",
-        _ => "Unknown",
+/// Print a single transaction in the requested format
+fn print_transaction(tx: &Transaction, opts: &RenderOptions, args: &Args) {
+    if args.format.is_serialized() {
+        print_serialized(&core_decode(tx, args.network), args.format);
+    } else if args.annotate_hex {
+        print!("{}", annotate_hex(tx, opts));
+    } else if args.explain {
+        println!("{}", explain_transaction(tx, opts));
+    } else if args.summary_table {
+        print!("{}", render_summary_table([(0, tx)], opts));
+    } else {
+        print_report(tx, opts);
     }
 }
 
-fn display_transaction(tx: &Transaction) {
-    // Transaction Overview
-    println!(
-        "\n{} {}",
-        "📋".bold(),
-        "TRANSACTION OVERVIEW".green().bold()
-    );
-    println!("{}", "─".repeat(70).green());
-
-    let mut overview = Table::new();
-    overview.set_format(*format::consts::FORMAT_CLEAN);
-
-    overview.add_row(Row::new(vec![
-        Cell::new("Transaction ID (txid)").style_spec("Fb"),
-        Cell::new(&tx.compute_txid().to_string()).style_spec("Fc"),
-    ]));
-    overview.add_row(Row::new(vec![
-        Cell::new("Version").style_spec("Fb"),
-        Cell::new(&format!("{}", tx.version.0)).style_spec("Fw"),
-    ]));
-    overview.add_row(Row::new(vec![
-        Cell::new("Lock Time").style_spec("Fb"),
-        Cell::new(&format!("{}", tx.lock_time)).style_spec("Fw"),
-    ]));
-    overview.add_row(Row::new(vec![
-        Cell::new("Size").style_spec("Fb"),
-        Cell::new(&format!("{} bytes", tx.total_size())).style_spec("Fw"),
-    ]));
-    overview.add_row(Row::new(vec![
-        Cell::new("Virtual Size").style_spec("Fb"),
-        Cell::new(&format!("{} vBytes", tx.vsize())).style_spec("Fw"),
-    ]));
-    overview.add_row(Row::new(vec![
-        Cell::new("Weight").style_spec("Fb"),
-        Cell::new(&format!("{} WU", tx.weight().to_wu())).style_spec("Fw"),
-    ]));
-
-    overview.printstd();
-
-    // Inputs
-    println!(
-        "\n{} {} ({})",
-        "📥".bold(),
-        "INPUTS".blue().bold(),
-        tx.input.len().to_string().yellow().bold()
-    );
-    println!("{}", "─".repeat(70).blue());
+/// Stream the full report to stdout as it is rendered
+fn print_report(tx: &Transaction, opts: &RenderOptions) {
+    let mut stdout = io::BufWriter::new(io::stdout().lock());
+    if let Err(e) = write_transaction(&mut stdout, tx, opts).and_then(|()| stdout.flush()) {
+        eprintln!("{} Failed to write the report: {}", "✗".red().bold(), e);
+    }
+}
 
-    for (idx, input) in tx.input.iter().enumerate() {
-        println!(
-            "\n{} {}",
-            "Input".blue().bold(),
-            format!("#{}", idx).yellow()
-        );
+/// Decode a PSBT and print its unsigned transaction, taking input amounts (and so the
+/// fee) from the PSBT's UTXO fields unless --prevout-values is given
+fn display_psbt(text: &str, args: &Args) -> Result<(), Exit> {
+    let psbt = decode_psbt(text).map_err(|e| {
+        eprintln!("{} {}", "✗".red().bold(), e);
+        Exit::DecodeFailed
+    })?;
+    let tx = &psbt.unsigned_tx;
+
+    let amounts = psbt_input_amounts(&psbt);
+    let prevout_values = if args.prevout_values.is_empty() {
+        amounts
+            .iter()
+            .copied()
+            .collect::<Option<Vec<u64>>>()
+            .unwrap_or_default()
+    } else {
+        args.prevout_values.clone()
+    };
+    if !prevout_values.is_empty()
+        && let Err(e) = compute_fee(tx, &prevout_values)
+    {
+        eprintln!("{} {}", "✗".red().bold(), e);
+        return Err(Exit::Failure);
+    }
 
-        let mut input_table = Table::new();
-        input_table.set_format(*format::consts::FORMAT_CLEAN);
-
-        // Detect and display input type
-        let input_type = detect_input_type(input);
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Type").style_spec("Fb"),
-            Cell::new(&input_type).style_spec("Fc"),
-        ]));
-
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Previous TX").style_spec("Fb"),
-            Cell::new(&input.previous_output.txid.to_string()).style_spec("Fw"),
-        ]));
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Output Index").style_spec("Fb"),
-            Cell::new(&format!("{}", input.previous_output.vout)).style_spec("Fw"),
-        ]));
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Script Length").style_spec("Fb"),
-            Cell::new(&format!("{} bytes", input.script_sig.len())).style_spec("Fw"),
-        ]));
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Script Sig").style_spec("Fb"),
-            Cell::new(&hex::encode(input.script_sig.as_bytes())).style_spec("Fd"),
-        ]));
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Sequence").style_spec("Fb"),
-            Cell::new(&format!("{}", input.sequence,)).style_spec("Fw"),
-        ]));
-
-        if let Some(timelock) = input.sequence.to_relative_lock_time() {
-            input_table.add_row(Row::new(vec![
-                Cell::new("  Timelock").style_spec("Fb"),
-                Cell::new(&format!("{:?}", timelock)).style_spec("Fw"),
-            ]));
-        }
+    print_transaction(
+        tx,
+        &render_options(args, prevout_values).input_amounts(amounts),
+        args,
+    );
+    check_expected_ids(tx, args)
+}
 
-        input_table.add_row(Row::new(vec![
-            Cell::new("  Virtual Size").style_spec("Fb"),
-            Cell::new(&format!("{} vBytes", input_vsize(input))).style_spec("Fw"),
-        ]));
-
-        // Witness data if present
-        if !input.witness.is_empty() {
-            input_table.add_row(Row::new(vec![
-                Cell::new("  Witness Items").style_spec("Fb"),
-                Cell::new(&format!("{}", input.witness.len())).style_spec("Fy"),
-            ]));
-
-            for (i, witness_item) in input.witness.iter().enumerate() {
-                let decoded = decode_witness_item(witness_item);
-                input_table.add_row(Row::new(vec![
-                    Cell::new(&format!("  Witness [{}]", i)).style_spec("Fb"),
-                    Cell::new(&format!(
-                        "{}\n    Type: {}",
-                        hex::encode(witness_item),
-                        decoded
-                    ))
-                    .style_spec("Fy"),
-                ]));
+/// Watch a file and re-render it whenever it changes, until interrupted
+fn watch_file(file_path: &str, args: &Args) -> ! {
+    let path = Path::new(file_path);
+    let redraw = || {
+        // Clear the screen and move the cursor home
+        print!("\x1B[2J\x1B[H");
+        match read_tx_file(file_path, args.input_format, args.gzip) {
+            Ok(contents) => {
+                let _ = display_file(&contents, args);
             }
+            Err((_, e)) => eprintln!("{} {}", "✗".red().bold(), e),
         }
+        eprintln!("\nWatching {} for changes, press Ctrl-C to stop", file_path);
+    };
 
-        input_table.printstd();
-    }
+    let (sender, receiver) = mpsc::channel();
+    // Watch the parent directory so editors that save by replacing the file are noticed
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    let _watcher = watcher.unwrap_or_else(|e| {
+        eprintln!("{} Failed to watch {}: {}", "✗".red().bold(), file_path, e);
+        Exit::Io.exit();
+    });
 
-    // Outputs
-    println!(
-        "\n{} {} ({})",
-        "📤".bold(),
-        "OUTPUTS".magenta().bold(),
-        tx.output.len().to_string().yellow().bold()
-    );
-    println!("{}", "─".repeat(70).magenta());
+    redraw();
+    for event in &receiver {
+        let Ok(event) = event else { continue };
+        let touches_file = event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name());
+        if touches_file && (event.kind.is_modify() || event.kind.is_create()) {
+            // A single save often fires several events; render once
+            thread::sleep(Duration::from_millis(50));
+            while receiver.try_recv().is_ok() {}
+            redraw();
+        }
+    }
+    Exit::Failure.exit();
+}
 
-    let total_output: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+/// Collect the display preferences from the command line
+fn render_options(args: &Args, prevout_values: Vec<u64>) -> RenderOptions {
+    RenderOptions::default()
+        .network(args.network)
+        .color(!args.no_color)
+        .thousands_sep(!args.no_thousands_sep)
+        .verbose(args.verbose)
+        .prevout_values(prevout_values)
+        .fee_buckets(args.fee_buckets)
+        .min_relay_fee_rate(args.min_relay_fee_rate)
+        .show_internal_hash(args.show_internal_hash)
+        .truncate(args.truncate)
+        .electrum(args.electrum)
+        .denomination(args.denomination)
+        .table_style(args.table_style)
+        .highlight_address(args.highlight_address.clone())
+        .max_witness_preview((args.max_witness_preview > 0).then_some(args.max_witness_preview))
+        .quiet(args.quiet)
+        .value_bars(args.value_bars)
+        .internal_key(args.internal_key)
+        .merkle_root(args.merkle_root)
+        .expected_txid(args.expected_txid)
+        .expected_wtxid(args.expected_wtxid)
+        .expected_height(args.expected_height)
+        .full_rbf(args.full_rbf)
+        .max_inputs_display(args.max_inputs_display)
+        .max_outputs_display(args.max_outputs_display)
+        .output_order(args.sort_display_by)
+        .histogram(args.histogram)
+        .confirmation(args.confirmation)
+}
 
-    for (idx, output) in tx.output.iter().enumerate() {
-        println!(
-            "\n{} {}",
-            "Output".magenta().bold(),
-            format!("#{}", idx).yellow()
-        );
+/// Whether only a one-line form of each transaction was asked for: --hex-only,
+/// --strip-witness, --txid-only or --wtxid-only
+fn echoes_only(args: &Args) -> bool {
+    args.hex_only || args.strip_witness || args.txid_only || args.wtxid_only
+}
 
-        let mut output_table = Table::new();
-        output_table.set_format(*format::consts::FORMAT_CLEAN);
-
-        let btc_value = output.value.to_sat() as f64 / 100_000_000.0;
-        output_table.add_row(Row::new(vec![
-            Cell::new("  Value").style_spec("Fb"),
-            Cell::new(&format!(
-                "{:.8} BTC ({} satoshis)",
-                btc_value,
-                output.value.to_sat()
-            ))
-            .style_spec("Fy"),
-        ]));
-
-        // Try to extract address from script
-        if let Ok(address) =
-            bitcoin::Address::from_script(&output.script_pubkey, bitcoin::Network::Bitcoin)
-        {
-            let addr_type = get_address_type(&address);
-            output_table.add_row(Row::new(vec![
-                Cell::new("  Address").style_spec("Fb"),
-                Cell::new(&format!("{} ({})", address, addr_type)).style_spec("Fc"),
-            ]));
-        } else if let Ok(address) =
-            bitcoin::Address::from_script(&output.script_pubkey, bitcoin::Network::Testnet)
-        {
-            let addr_type = get_address_type(&address);
-            output_table.add_row(Row::new(vec![
-                Cell::new("  Address (Testnet)").style_spec("Fb"),
-                Cell::new(&format!("{} ({})", address, addr_type)).style_spec("Fc"),
-            ]));
+/// Print the one-line form of every transaction, failing with the exit code of the first
+/// error
+fn echo_transactions(tx_hexes: &[String], args: &Args) -> Result<(), Exit> {
+    let mut result = Ok(());
+    for tx_hex in tx_hexes {
+        match decode_hex_transaction(tx_hex, args.max_size) {
+            Ok((tx, tx_bytes)) => echo_transaction(&tx, &tx_bytes, args),
+            Err(e) => {
+                result = result.and(Err(Exit::for_decode_error(tx_hex)));
+                eprintln!("{} {}", "✗".red().bold(), e);
+            }
         }
+    }
+    result
+}
 
-        // Check if this is an ephemeral anchor (P2A)
-        if is_ephemeral_anchor(output) {
-            output_table.add_row(Row::new(vec![
-                Cell::new("  Type").style_spec("Fb"),
-                Cell::new("⚓ Ephemeral Anchor (P2A) - Pay-to-Anchor").style_spec("Fy"),
-            ]));
-            output_table.add_row(Row::new(vec![
-                Cell::new("  Address").style_spec("Fb"),
-                Cell::new("bc1pfeessrawgf").style_spec("Fc"),
-            ]));
-            output_table.add_row(Row::new(vec![
-                Cell::new("  Purpose").style_spec("Fb"),
-                Cell::new("Anyone-can-spend anchor for CPFP fee bumping").style_spec("Fd"),
-            ]));
+/// Print the canonical serialization, the witness-stripped serialization, or the txid
+/// (or wtxid) of a transaction decoded from `tx_bytes`
+fn echo_transaction(tx: &Transaction, tx_bytes: &[u8], args: &Args) {
+    if args.hex_only {
+        let canonical = serialize(tx);
+        println!("{}", hex::encode(&canonical));
+        if canonical != tx_bytes {
+            eprintln!("input was non-canonical");
         }
-        output_table.add_row(Row::new(vec![
-            Cell::new("  Script Length").style_spec("Fb"),
-            Cell::new(&format!("{} bytes", output.script_pubkey.len())).style_spec("Fw"),
-        ]));
-        output_table.add_row(Row::new(vec![
-            Cell::new("  Script PubKey").style_spec("Fb"),
-            Cell::new(&format!("{}", output.script_pubkey.to_asm_string())).style_spec("Fg"),
-        ]));
-        output_table.add_row(Row::new(vec![
-            Cell::new("  Script Hex").style_spec("Fb"),
-            Cell::new(&hex::encode(output.script_pubkey.as_bytes())).style_spec("Fg"),
-        ]));
-        output_table.add_row(Row::new(vec![
-            Cell::new("  Virtual Size").style_spec("Fb"),
-            Cell::new(&format!("{} vBytes", output_vsize(output))).style_spec("Fw"),
-        ]));
-
-        output_table.printstd();
-    }
-
-    // Summary
-    println!("\n{} {}", "💰".bold(), "SUMMARY".yellow().bold());
-    println!("{}", "─".repeat(70).yellow());
-
-    let mut summary = Table::new();
-    summary.set_format(*format::consts::FORMAT_CLEAN);
-
-    let total_btc = total_output as f64 / 100_000_000.0;
-    summary.add_row(Row::new(vec![
-        Cell::new("Total Output Value").style_spec("Fb"),
-        Cell::new(&format!("{:.8} BTC ({} satoshis)", total_btc, total_output)).style_spec("Fy"),
-    ]));
-    summary.add_row(Row::new(vec![
-        Cell::new("Number of Inputs").style_spec("Fb"),
-        Cell::new(&tx.input.len().to_string()).style_spec("Fw"),
-    ]));
-    summary.add_row(Row::new(vec![
-        Cell::new("Number of Outputs").style_spec("Fb"),
-        Cell::new(&tx.output.len().to_string()).style_spec("Fw"),
-    ]));
-
-    summary.printstd();
-
-    println!("\n{}", "═".repeat(70).cyan().bold());
-    println!();
+    } else if args.strip_witness {
+        println!("{}", hex::encode(strip_witness(tx)));
+        eprintln!(
+            "double SHA-256 of this hex, byte-reversed, is the txid {}",
+            tx.compute_txid()
+        );
+    } else if args.wtxid_only {
+        println!("{}", tx.compute_wtxid());
+    } else {
+        println!("{}", tx.compute_txid());
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // P2WPKH segwit transaction with 1 input and 3 outputs
-    const SEGWIT_TX_HEX: &str = "020000000001010eeb61beeddeaab8a7bb024efcac1fa3faecb7c96c4127782e6bc7cd59fc51490200000000fffffffd03afd701000000000017a914715a091837e1340c8f4d11c20a16a4c92cee9af187ce22000000000000225120a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208aef780800000000001600148db324a5c4bf820717091087769dee302809ccb202483045022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f3140220283339f2abbd165bbc6b4b305db28b9a064f051c2b097f318c2fa507bf320bc3012103cc976f202ab9e2d0bbe3ed8e728aadd6042294f223ff665516c114733647b6ba00000000";
-
-    // Coinbase segwit transaction with 1 input and 2 outputs
-    const COINBASE_TX_HEX: &str = "020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff0502e8030101ffffffff0200f2052a0100000016001496d5599e55dfb1d6a2adc94e4f7e3b0f6b3b6b100000000000000000266a24aa21a9ede2f61c3f71d1defd3fa999dfa36953755c690689799962b48bebd836974e8cf90120000000000000000000000000000000000000000000000000000000000000000000000000";
-
-    #[test]
-    fn test_compact_size_len() {
-        assert_eq!(compact_size_len(0), 1);
-        assert_eq!(compact_size_len(1), 1);
-        assert_eq!(compact_size_len(252), 1);
-        assert_eq!(compact_size_len(253), 3);
-        assert_eq!(compact_size_len(0xffff), 3);
-        assert_eq!(compact_size_len(0x10000), 5);
-        assert_eq!(compact_size_len(0xffff_ffff), 5);
-        assert_eq!(compact_size_len(0x1_0000_0000), 9);
-    }
+/// Decode a hex-encoded transaction, keeping its bytes to compare with the canonical
+/// serialization
+fn decode_hex_transaction(tx_hex: &str, max_size: usize) -> Result<(Transaction, Vec<u8>), String> {
+    let tx_bytes = decode_hex_limited(tx_hex, max_size)?;
+    let tx = decode_transaction_bytes(&tx_bytes)?;
+    Ok((tx, tx_bytes))
+}
 
-    #[test]
-    fn test_input_vsize_segwit() {
-        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
-        // P2WPKH input: 41 bytes base (36 + 1 + 0 + 4), witness ~107 bytes
-        // Weight = 41*4 + 107 = 271, vsize = ceil(271/4) = 68
-        assert_eq!(input_vsize(&tx.input[0]), 68);
+/// Print a value as JSON, indented like `bitcoin-cli` does unless compact output was
+/// asked for or stdout is piped, or as MessagePack
+fn print_serialized<T: serde::Serialize>(value: &T, format: OutputFormat) {
+    if format == OutputFormat::Msgpack {
+        // Structs are encoded as maps so the keys match the JSON output. The bytes are
+        // written as they are, without a trailing newline.
+        let bytes = rmp_serde::to_vec_named(value).expect("decoded transactions serialize");
+        let mut stdout = io::stdout().lock();
+        if let Err(e) = stdout.write_all(&bytes).and_then(|()| stdout.flush()) {
+            eprintln!("{} {}", "✗".red().bold(), e);
+        }
+        return;
     }
+    let pretty = match format {
+        OutputFormat::CoreJsonCompact => false,
+        OutputFormat::CoreJsonPretty => true,
+        _ => io::stdout().is_terminal(),
+    };
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    println!("{}", json.expect("decoded transactions serialize to JSON"));
+}
 
-    #[test]
-    fn test_input_vsize_coinbase() {
-        let tx = decode_transaction(COINBASE_TX_HEX).unwrap();
-        // Coinbase input: base = 36 + 1 + 5 + 4 = 46, witness = 1 + 1 + 32 = 34
-        // Weight = 46*4 + 34 = 218, vsize = ceil(218/4) = 55
-        assert_eq!(input_vsize(&tx.input[0]), 55);
-    }
+/// Decode and display every transaction of a batch, failing with the exit code of the
+/// first error
+fn decode_batch(
+    tx_hexes: &[String],
+    opts: &RenderOptions,
+    args: &Args,
+    show_progress: bool,
+) -> Result<(), Exit> {
+    let total = tx_hexes.len();
+    let mut failed = 0;
+    let mut result = Ok(());
+    // Everything is decoded before printing so spends between transactions can be linked
+    let mut decoded = Vec::new();
+
+    for (idx, tx_hex) in tx_hexes.iter().enumerate() {
+        let label = format!("Transaction #{}: ", idx);
+        match decode_hex_transaction(tx_hex, args.max_size) {
+            Ok((tx, tx_bytes)) => match check_roundtrip(&tx, &tx_bytes, args.strict, &label) {
+                Ok(()) => decoded.push((idx, tx)),
+                Err(code) => {
+                    failed += 1;
+                    result = result.and(Err(code));
+                }
+            },
+            Err(e) => {
+                failed += 1;
+                result = result.and(Err(Exit::for_decode_error(tx_hex)));
+                if show_progress {
+                    // Move past the progress line before reporting the error
+                    eprintln!();
+                }
+                eprintln!("{} Transaction #{}: {}", "✗".red().bold(), idx, e);
+            }
+        }
 
-    #[test]
-    fn test_output_vsize_p2sh() {
-        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
-        // P2SH output: 8 + 1 + 23 = 32
-        assert_eq!(output_vsize(&tx.output[0]), 32);
+        if show_progress {
+            eprint!("\rDecoded {}/{}", idx + 1, total);
+        }
     }
 
-    #[test]
-    fn test_output_vsize_p2tr() {
-        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
-        // P2TR output: 8 + 1 + 34 = 43
-        assert_eq!(output_vsize(&tx.output[1]), 43);
+    if show_progress {
+        eprintln!();
     }
 
-    #[test]
-    fn test_output_vsize_p2wpkh() {
-        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
-        // P2WPKH output: 8 + 1 + 22 = 31
-        assert_eq!(output_vsize(&tx.output[2]), 31);
+    if args.format.is_serialized() {
+        // JSON and MessagePack output is a single array so it stays parseable
+        let json: Vec<_> = decoded
+            .iter()
+            .map(|(_, tx)| core_decode(tx, args.network))
+            .collect();
+        print_serialized(&json, args.format);
+    } else {
+        let edges = package_edges(decoded.iter().map(|(idx, tx)| (*idx, tx)));
+        // Batches default to the summary table unless another rendering was asked for
+        let summary_table =
+            args.summary_table || !(args.no_summary_table || args.explain || args.annotate_hex);
+        if summary_table {
+            print!(
+                "{}",
+                render_summary_table(decoded.iter().map(|(idx, tx)| (*idx, tx)), opts)
+            );
+        }
+        for (idx, tx) in decoded.iter().filter(|_| !summary_table) {
+            println!(
+                "\n{} {}",
+                "Transaction".cyan().bold(),
+                format!("#{}", idx).yellow()
+            );
+            if args.annotate_hex {
+                print!("{}", annotate_hex(tx, opts));
+            } else if args.explain {
+                println!("{}", explain_transaction(tx, opts));
+            } else {
+                let opts = opts.clone().package(Some(PackageContext {
+                    index: *idx,
+                    edges: edges.clone(),
+                }));
+                print_report(tx, &opts);
+            }
+        }
+        if !edges.is_empty() && !args.explain && !args.annotate_hex {
+            print!("{}", render_package_relationships(&edges, opts));
+        }
+        if args.diff_prevout_set {
+            let conflicts = batch_conflicts(decoded.iter().map(|(idx, tx)| (*idx, tx)));
+            if !conflicts.is_empty() {
+                print!("{}", render_conflicts(&conflicts, opts));
+            }
+        }
     }
+    eprintln!(
+        "{} transactions: {} succeeded, {} failed",
+        total,
+        (total - failed).to_string().green(),
+        failed.to_string().red()
+    );
 
-    #[test]
-    fn test_vsize_sum_plus_overhead_equals_tx_vsize() {
-        let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
-
-        let inputs_vsize: usize = tx.input.iter().map(|i| input_vsize(i)).sum();
-        let outputs_vsize: usize = tx.output.iter().map(|o| output_vsize(o)).sum();
-
-        // Transaction overhead:
-        // base: version(4) + input_count(1) + output_count(1) + locktime(4) = 10
-        // witness: marker(1) + flag(1) = 2
-        // overhead weight = 10*4 + 2 = 42, overhead vsize = ceil(42/4) = 11
-        let overhead_base = 4 + compact_size_len(tx.input.len()) + compact_size_len(tx.output.len()) + 4;
-        let overhead_witness = 2; // segwit marker + flag
-        let overhead_weight = overhead_base * 4 + overhead_witness;
-        let overhead_vsize = (overhead_weight + 3) / 4;
-
-        assert_eq!(inputs_vsize + outputs_vsize + overhead_vsize, tx.vsize());
-    }
+    result
 }
@@ -0,0 +1,87 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Minimal Bitcoin Core JSON-RPC client for fetching raw transactions
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bitcoin::Txid;
+use serde_json::{Value, json};
+use std::fs;
+use std::str::FromStr;
+
+/// Bitcoin Core error code for an unknown transaction (RPC_INVALID_ADDRESS_OR_KEY)
+const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+
+/// Credentials used to authenticate against the node
+pub enum RpcAuth {
+    /// Path to the `.cookie` file written by bitcoind
+    Cookie(String),
+    /// Plain `user:pass` credentials
+    UserPass(String),
+    /// No authentication header
+    None,
+}
+
+impl RpcAuth {
+    /// Resolve the `user:pass` string sent with HTTP basic auth
+    fn credentials(&self) -> Result<Option<String>, String> {
+        match self {
+            RpcAuth::Cookie(path) => fs::read_to_string(path)
+                .map(|contents| Some(contents.trim().to_string()))
+                .map_err(|e| format!("Failed to read RPC cookie file {}: {}", path, e)),
+            RpcAuth::UserPass(creds) => Ok(Some(creds.clone())),
+            RpcAuth::None => Ok(None),
+        }
+    }
+}
+
+/// Fetch the hex-encoded transaction with the given txid via `getrawtransaction`
+pub fn get_raw_transaction(url: &str, auth: &RpcAuth, txid: &str) -> Result<String, String> {
+    let txid = Txid::from_str(txid).map_err(|e| format!("Invalid txid {}: {}", txid, e))?;
+
+    let body = json!({
+        "jsonrpc": "1.0",
+        "id": "bitcoin-tx-decoder",
+        "method": "getrawtransaction",
+        "params": [txid.to_string()],
+    });
+
+    let mut request = ureq::post(url).set("Content-Type", "application/json");
+    if let Some(creds) = auth.credentials()? {
+        request = request.set(
+            "Authorization",
+            &format!("Basic {}", STANDARD.encode(creds)),
+        );
+    }
+
+    let response = match request.send_string(&body.to_string()) {
+        Ok(response) => response,
+        Err(ureq::Error::Status(401, _)) => {
+            return Err("RPC authentication failed; check --rpc-cookie or --rpc-auth".to_string());
+        }
+        // Bitcoin Core reports RPC errors with a non-2xx status and a JSON body
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(e)) => {
+            return Err(format!("RPC unreachable at {}: {}", url, e));
+        }
+    };
+
+    let reply: Value = response
+        .into_string()
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .ok_or_else(|| "RPC returned a malformed response".to_string())?;
+
+    if let Some(error) = reply.get("error").filter(|e| !e.is_null()) {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        return if error["code"].as_i64() == Some(RPC_INVALID_ADDRESS_OR_KEY) {
+            Err(format!("No such transaction {}: {}", txid, message))
+        } else {
+            Err(format!("RPC error: {}", message))
+        };
+    }
+
+    reply["result"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| "RPC response is missing the transaction hex".to_string())
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Fuzz-style tests checking that no input makes the public API panic. Inputs come
+//! from a seeded generator so any failure is reproducible.
+
+use bitcoin::{Amount, ScriptBuf, Sequence, Transaction, Witness, absolute, transaction};
+use bitcoin_tx_decoder::{
+    RenderOptions, core_decode, decode_transaction, decode_transaction_bytes, explain_transaction,
+    render_summary_table, render_transaction,
+};
+
+// P2WPKH segwit transaction with 1 input and 3 outputs
+const SEGWIT_TX_HEX: &str = "020000000001010eeb61beeddeaab8a7bb024efcac1fa3faecb7c96c4127782e6bc7cd59fc51490200000000fffffffd03afd701000000000017a914715a091837e1340c8f4d11c20a16a4c92cee9af187ce22000000000000225120a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208aef780800000000001600148db324a5c4bf820717091087769dee302809ccb202483045022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f3140220283339f2abbd165bbc6b4b305db28b9a064f051c2b097f318c2fa507bf320bc3012103cc976f202ab9e2d0bbe3ed8e728aadd6042294f223ff665516c114733647b6ba00000000";
+
+/// Small xorshift generator, enough to spread inputs without extra dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, max_len: usize) -> Vec<u8> {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+/// Render a decoded transaction every way the CLI can
+fn render_all(tx: &Transaction) {
+    let opts = RenderOptions {
+        color: false,
+        verbose: true,
+        ..RenderOptions::default()
+    };
+    render_transaction(tx, &opts);
+    explain_transaction(tx, &opts);
+    render_summary_table([(0, tx)], &opts);
+    core_decode(tx, opts.network);
+}
+
+#[test]
+fn random_hex_never_panics() {
+    let mut rng = Rng(0x5eed);
+    for _ in 0..5_000 {
+        let text: String = (0..rng.below(200))
+            .map(|_| b"0123456789abcdefXYZ "[rng.below(20)] as char)
+            .collect();
+        if let Ok(tx) = decode_transaction(&text) {
+            render_all(&tx);
+        }
+    }
+}
+
+#[test]
+fn mutated_bytes_never_panic() {
+    let mut rng = Rng(0xdec0de);
+    let original = hex::decode(SEGWIT_TX_HEX).unwrap();
+    for _ in 0..5_000 {
+        let mut bytes = original.clone();
+        for _ in 0..=rng.below(4) {
+            let pos = rng.below(bytes.len());
+            match rng.below(3) {
+                0 => bytes[pos] = rng.next() as u8,
+                1 => bytes.truncate(pos),
+                _ => bytes.insert(pos, rng.next() as u8),
+            }
+            if bytes.is_empty() {
+                break;
+            }
+        }
+        if let Ok(tx) = decode_transaction_bytes(&bytes) {
+            render_all(&tx);
+        }
+    }
+}
+
+#[test]
+fn random_scripts_and_witnesses_never_panic() {
+    let mut rng = Rng(0xb17c0171);
+    let template = decode_transaction(SEGWIT_TX_HEX).unwrap();
+    for _ in 0..2_000 {
+        let mut tx = template.clone();
+        tx.version = transaction::Version(rng.next() as i32);
+        tx.lock_time = absolute::LockTime::from_consensus(rng.next() as u32);
+        for input in &mut tx.input {
+            input.script_sig = ScriptBuf::from_bytes(rng.bytes(120));
+            let items: Vec<Vec<u8>> = (0..rng.below(5)).map(|_| rng.bytes(80)).collect();
+            input.witness = Witness::from_slice(&items);
+            input.sequence = Sequence(rng.next() as u32);
+        }
+        for output in &mut tx.output {
+            output.value = Amount::from_sat(rng.next() % 2_100_000_000_000_000);
+            output.script_pubkey = ScriptBuf::from_bytes(rng.bytes(60));
+        }
+        render_all(&tx);
+    }
+}
+
+#[test]
+fn out_of_range_values_never_panic() {
+    // Values past 21M BTC decode fine, and their total doesn't fit in a u64
+    let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+    for output in &mut tx.output {
+        output.value = Amount::from_sat(u64::MAX);
+    }
+    render_all(&tx);
+    tx.output[1].script_pubkey = ScriptBuf::from_bytes(vec![0x6a]);
+    tx.output[2].script_pubkey = ScriptBuf::from_bytes(vec![0x6a]);
+    render_all(&tx);
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2025 Oleg Kubrakov
+
+//! Golden tests for the rendered report. Run with `UPDATE_GOLDEN=1` to regenerate
+//! the files in `tests/golden` after an intended formatting change.
+
+use bitcoin_tx_decoder::{
+    RenderOptions, core_decode, decode_transaction, render_transaction, write_transaction,
+};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Legacy P2PKH transaction with 1 input and 1 output
+const LEGACY_TX_HEX: &str = "0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000";
+
+// P2WPKH segwit transaction with 1 input and 3 outputs
+const SEGWIT_TX_HEX: &str = "020000000001010eeb61beeddeaab8a7bb024efcac1fa3faecb7c96c4127782e6bc7cd59fc51490200000000fffffffd03afd701000000000017a914715a091837e1340c8f4d11c20a16a4c92cee9af187ce22000000000000225120a76dcc4ffe5f6120fb0e78332d02272de196d2bc75fbb2f31908ea68fc88208aef780800000000001600148db324a5c4bf820717091087769dee302809ccb202483045022100fea069372ab582b1edfa863c3affdf691064bd892a14173a1f3bc7285497f3140220283339f2abbd165bbc6b4b305db28b9a064f051c2b097f318c2fa507bf320bc3012103cc976f202ab9e2d0bbe3ed8e728aadd6042294f223ff665516c114733647b6ba00000000";
+
+// Taproot key path spend with 1 input and 2 outputs
+const TAPROOT_TX_HEX: &str = "01000000000101b5cee87f1a60915c38bb0bc26aaf2b67be2b890bbc54bb4be1e40272e0d2fe0b0000000000ffffffff025529000000000000225120106daad8a5cb2e6fc74783714273bad554a148ca2d054e7a19250e9935366f3033760000000000002200205e6d83c44f57484fd2ef2a62b6d36cdcd6b3e06b661e33fd65588a28ad0dbe060141df9d1bfce71f90d68bf9e9461910b3716466bfe035c7dbabaa7791383af6c7ef405a3a1f481488a91d33cd90b098d13cb904323a3e215523aceaa04e1bb35cdb0100000000";
+
+/// Strip trailing padding so the comparison doesn't depend on column widths at line ends
+fn normalize(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_end().to_string() + "\n"
+}
+
+fn assert_golden(name: &str, tx_hex: &str) {
+    let tx = decode_transaction(tx_hex).unwrap();
+    let opts = RenderOptions {
+        color: false,
+        ..RenderOptions::default()
+    };
+    compare_golden(&format!("{}.txt", name), &render_transaction(&tx, &opts));
+}
+
+/// Compare with the `bitcoin-cli decoderawtransaction` style JSON in `tests/golden`
+fn assert_json_golden(name: &str, tx: &bitcoin::Transaction) {
+    let json = serde_json::to_string_pretty(&core_decode(tx, bitcoin::Network::Bitcoin)).unwrap();
+    compare_golden(&format!("{}.json", name), &json);
+}
+
+fn compare_golden(file_name: &str, output: &str) {
+    let rendered = normalize(output);
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(file_name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &rendered).unwrap();
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        rendered,
+        normalize(&expected),
+        "rendered output differs from {}",
+        path.display()
+    );
+}
+
+#[test]
+fn test_render_legacy_golden() {
+    assert_golden("legacy", LEGACY_TX_HEX);
+}
+
+#[test]
+fn test_render_segwit_v0_golden() {
+    assert_golden("segwit_v0", SEGWIT_TX_HEX);
+}
+
+#[test]
+fn test_render_taproot_golden() {
+    assert_golden("taproot", TAPROOT_TX_HEX);
+}
+
+#[test]
+fn test_core_json_one_sat_output_golden() {
+    // Values are printed with all 8 decimals, not as `1e-8`
+    let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+    tx.output[1].value = bitcoin::Amount::from_sat(1);
+    tx.output[2].value = bitcoin::Amount::MAX_MONEY;
+    assert_json_golden("core_json_one_sat", &tx);
+}
+
+#[test]
+fn test_write_transaction_matches_render() {
+    let tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+    let opts = RenderOptions::default();
+    let mut streamed = Vec::new();
+    write_transaction(&mut streamed, &tx, &opts).unwrap();
+    assert_eq!(
+        String::from_utf8(streamed).unwrap(),
+        render_transaction(&tx, &opts)
+    );
+}
+
+/// Records the largest single write, to check that the report is never buffered whole
+#[derive(Default)]
+struct LargestWrite {
+    total: usize,
+    largest: usize,
+}
+
+impl io::Write for LargestWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.total += buf.len();
+        self.largest = self.largest.max(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_transaction_streams_outputs() {
+    let mut tx = decode_transaction(SEGWIT_TX_HEX).unwrap();
+    let template = tx.output[2].clone();
+    tx.output = (0..10_000u64)
+        .map(|n| bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(1_000 + n),
+            ..template.clone()
+        })
+        .collect();
+
+    let opts = RenderOptions {
+        color: false,
+        ..RenderOptions::default()
+    };
+    let mut out = LargestWrite::default();
+    write_transaction(&mut out, &tx, &opts).unwrap();
+    assert!(out.total > 4_000_000, "{} bytes rendered", out.total);
+    // Each output is written on its own; the overview with its warnings is the largest
+    assert!(
+        out.largest < 64 * 1024,
+        "{} bytes in one write",
+        out.largest
+    );
+}